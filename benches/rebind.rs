@@ -0,0 +1,79 @@
+//! Compares rebinding bound values using `Bound::rebind` with fully reconstructing them.
+//!
+//! Run with `cargo bench`.
+// benchmarks are not covered by the minimal supported rust version
+#![allow(clippy::incompatible_msrv)]
+#[macro_use]
+extern crate galemu;
+
+use std::{
+    hint::black_box,
+    time::Instant
+};
+
+use galemu::Bound;
+
+const ITERATIONS: u32 = 1_000_000;
+const BUFFER_SIZE: usize = 4096;
+
+struct Connection {
+    id: usize
+}
+
+struct Statement<'conn> {
+    conn: &'conn Connection,
+    buffer: Vec<u8>
+}
+
+impl<'conn> Statement<'conn> {
+    fn prepare(conn: &'conn Connection) -> Self {
+        Statement { conn, buffer: Vec::with_capacity(BUFFER_SIZE) }
+    }
+
+    fn reset<'n>(self, conn: &'n Connection) -> Statement<'n> {
+        let mut buffer = self.buffer;
+        buffer.clear();
+        Statement { conn, buffer }
+    }
+
+    fn execute(&mut self) {
+        self.buffer.extend_from_slice(&self.conn.id.to_le_bytes());
+    }
+}
+
+create_gal_wrapper_type!{ struct StatementWrap(Statement<'a>); }
+
+fn run(name: &str, f: impl FnOnce()) {
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    let per_iter = elapsed.as_nanos() / u128::from(ITERATIONS);
+    println!("{:<12} {:>10?} total, {:>5} ns/iter", name, elapsed, per_iter);
+}
+
+fn reconstruct(conns: &[Connection]) {
+    for idx in 0..ITERATIONS {
+        let conn = &conns[idx as usize % conns.len()];
+        let mut stmt = StatementWrap::new(Statement::prepare(conn));
+        StatementWrap::get_mut(&mut stmt).execute();
+        black_box(&stmt);
+    }
+}
+
+fn rebind(conns: &[Connection]) {
+    let mut stmt = StatementWrap::new(Statement::prepare(&conns[0]));
+    for idx in 0..ITERATIONS {
+        let conn = &conns[idx as usize % conns.len()];
+        stmt = Bound::rebind(stmt, |old| old.reset(conn));
+        StatementWrap::get_mut(&mut stmt).execute();
+        black_box(&stmt);
+    }
+}
+
+fn main() {
+    let conns = (0..8).map(|id| Connection { id }).collect::<Vec<_>>();
+    // warm up
+    reconstruct(&conns[..1]);
+    run("reconstruct", || reconstruct(&conns));
+    run("rebind", || rebind(&conns));
+}
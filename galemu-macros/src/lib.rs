@@ -0,0 +1,297 @@
+//! Procedural macros for `galemu`.
+//!
+//! Do not depend on this crate directly, the macros are re-exported by `galemu`
+//! behind cargo features (e.g. `#[galemu::async_trait]` behind `async_trait`).
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote,
+    visit_mut::{self, VisitMut},
+    Block, FnArg, GenericParam, ImplItem, Item, Lifetime, ParenthesizedGenericArguments, Pat,
+    PatIdent, ReturnType, Signature, TraitItem, Type, TypeBareFn, TypeReference, WherePredicate
+};
+
+/// The lifetime of the boxed futures returned by the rewritten methods.
+const FUTURE_LIFETIME: &str = "'galemu_async";
+
+/// Rewrites the `async fn`s of a trait or trait impl into functions returning boxed futures.
+///
+/// See the documentation of the re-export in `galemu`.
+#[proc_macro_attribute]
+pub fn async_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    let send = match parse_args(args.into()) {
+        Ok(send) => send,
+        Err(err) => return err.to_compile_error().into()
+    };
+    let mut item = parse_macro_input!(input as Item);
+    match expand_item(&mut item, send) {
+        Ok(()) => quote!(#item).into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+/// Returns true if the futures have to be `Send` (the default) and false for `?Send`.
+fn parse_args(args: TokenStream2) -> syn::Result<bool> {
+    if args.is_empty() {
+        return Ok(true);
+    }
+    let not_send: syn::Result<(syn::Token![?], syn::Ident)> = syn::parse::Parser::parse2(
+        |input: syn::parse::ParseStream| Ok((input.parse()?, input.parse()?)),
+        args.clone()
+    );
+    match not_send {
+        Ok((_, ident)) if ident == "Send" => Ok(false),
+        _ => Err(syn::Error::new_spanned(args, "expected nothing or `?Send`"))
+    }
+}
+
+fn expand_item(item: &mut Item, send: bool) -> syn::Result<()> {
+    match item {
+        Item::Trait(item) => {
+            for trait_item in &mut item.items {
+                if let TraitItem::Fn(method) = trait_item {
+                    if method.sig.asyncness.is_some() {
+                        expand_fn(&mut method.sig, method.default.as_mut(), send, true);
+                    }
+                }
+            }
+            Ok(())
+        },
+        Item::Impl(item) => {
+            for impl_item in &mut item.items {
+                if let ImplItem::Fn(method) = impl_item {
+                    if method.sig.asyncness.is_some() {
+                        expand_fn(&mut method.sig, Some(&mut method.block), send, false);
+                    }
+                }
+            }
+            Ok(())
+        },
+        other => Err(syn::Error::new_spanned(other, "expected a trait or a trait impl"))
+    }
+}
+
+/// Turns `async fn f(&self, ..) -> R` into `fn f<'life0, 'galemu_async>(&'life0 self, ..)
+/// -> Pin<Box<dyn Future<Output = R> + Send + 'galemu_async>>` and wraps the body (if any)
+/// into a boxed `async move` block.
+fn expand_fn(sig: &mut Signature, body: Option<&mut Block>, send: bool, is_trait: bool) {
+    sig.asyncness = None;
+
+    // name all elided lifetimes of the inputs, as the future captures all of them
+    let mut added = Vec::new();
+    let mut receiver_lifetime = None;
+    {
+        let mut elide = Elided(|| {
+            let lifetime = Lifetime::new(&format!("'life{}", added.len()), Span::call_site());
+            added.push(lifetime.clone());
+            lifetime
+        });
+        for input in &mut sig.inputs {
+            match input {
+                FnArg::Receiver(receiver) => {
+                    if let Some((_, lifetime)) = &mut receiver.reference {
+                        let lifetime = lifetime.get_or_insert_with(&mut elide.0).clone();
+                        if let Type::Reference(ty) = &mut *receiver.ty {
+                            ty.lifetime = Some(lifetime.clone());
+                        }
+                        receiver_lifetime = Some(lifetime);
+                    } else {
+                        elide.visit_type_mut(&mut receiver.ty);
+                        if let Type::Reference(ty) = &*receiver.ty {
+                            receiver_lifetime = ty.lifetime.clone();
+                        }
+                    }
+                },
+                FnArg::Typed(arg) => elide.visit_type_mut(&mut arg.ty)
+            }
+        }
+    }
+
+    // elided lifetimes in the output refer to the receiver or the only input lifetime
+    let mut output = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone()
+    };
+    let output_lifetime = receiver_lifetime.or_else(|| {
+        let mut collect = Collected(Vec::new());
+        for input in &mut sig.inputs {
+            collect.visit_fn_arg_mut(input);
+        }
+        if collect.0.len() == 1 { collect.0.pop() } else { None }
+    });
+    if let Some(lifetime) = output_lifetime {
+        Elided(|| lifetime.clone()).visit_type_mut(&mut output);
+    }
+
+    // everything captured by the future has to outlive it
+    let future_lifetime = Lifetime::new(FUTURE_LIFETIME, Span::call_site());
+    let lifetime_count = sig.generics.lifetimes().count();
+    for (idx, lifetime) in added.iter().enumerate() {
+        sig.generics.params.insert(lifetime_count + idx, parse_quote!(#lifetime));
+    }
+    let mut predicates: Vec<WherePredicate> = sig.generics.params.iter()
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(parse_quote!(#lifetime: #future_lifetime))
+            },
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(parse_quote!(#ident: #future_lifetime))
+            },
+            GenericParam::Const(_) => None
+        })
+        .collect();
+    predicates.push(parse_quote!(Self: #future_lifetime));
+    if is_trait && send && body.is_some() {
+        // the default body is type checked for all implementors
+        match sig.receiver() {
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_none() =>
+                predicates.push(parse_quote!(Self: ::std::marker::Sync)),
+            Some(_) => predicates.push(parse_quote!(Self: ::std::marker::Send)),
+            None => {}
+        }
+    }
+    sig.generics.params.insert(lifetime_count + added.len(), parse_quote!(#future_lifetime));
+    sig.generics.make_where_clause().predicates.extend(predicates);
+
+    let send_bound = if send { quote!(+ ::std::marker::Send) } else { quote!() };
+    sig.output = parse_quote! {
+        -> ::std::pin::Pin<::std::boxed::Box<
+            dyn ::std::future::Future<Output = #output> #send_bound + #future_lifetime
+        >>
+    };
+
+    if let Some(body) = body {
+        let moves = move_args(sig);
+        let inner = if output == parse_quote!(()) {
+            quote!(#body)
+        } else {
+            quote! {
+                let __ret: #output = #body;
+                #[allow(unreachable_code)]
+                __ret
+            }
+        };
+        *body = parse_quote!({
+            ::std::boxed::Box::pin(async move {
+                #(#moves)*
+                #inner
+            })
+        });
+    }
+}
+
+/// Moves all arguments into the future like `async fn` does, also for unused ones.
+///
+/// Arguments with patterns other than a plain binding are renamed and destructured
+/// inside of the future.
+fn move_args(sig: &mut Signature) -> Vec<TokenStream2> {
+    let mut moves = Vec::new();
+    for (idx, input) in sig.inputs.iter_mut().enumerate() {
+        let arg = match input {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(_) => continue
+        };
+        match &mut *arg.pat {
+            Pat::Ident(PatIdent { by_ref: None, mutability, ident, subpat: None, .. }) => {
+                let mutability = mutability.take();
+                moves.push(quote!(let #mutability #ident = #ident;));
+            },
+            pat => {
+                let name = format_ident!("__arg{}", idx);
+                moves.push(quote!(let #pat = #name;));
+                *pat = parse_quote!(#name);
+            }
+        }
+    }
+    moves
+}
+
+/// Replaces elided lifetimes with the ones returned by the closure.
+struct Elided<F>(F);
+
+impl<F: FnMut() -> Lifetime> VisitMut for Elided<F> {
+
+    fn visit_type_reference_mut(&mut self, ty: &mut TypeReference) {
+        if ty.lifetime.is_none() {
+            ty.lifetime = Some((self.0)());
+        }
+        visit_mut::visit_type_reference_mut(self, ty)
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = (self.0)();
+        }
+    }
+
+    // fn pointers and `Fn` traits have their own elision scope
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
+
+/// Collects all distinct lifetimes.
+struct Collected(Vec<Lifetime>);
+
+impl VisitMut for Collected {
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if !self.0.contains(lifetime) {
+            self.0.push(lifetime.clone());
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(mut item: Item) -> String {
+        expand_item(&mut item, true).unwrap();
+        quote!(#item).to_string()
+    }
+
+    #[test]
+    fn elided_lifetimes_are_named() {
+        let expanded = expand(parse_quote! {
+            trait Connection {
+                type Transaction;
+                async fn begin(&mut self, name: &str) -> Result<Bound<'_, Self::Transaction>, ()>;
+            }
+        });
+        let expected = quote! {
+            fn begin<'life0, 'life1, 'galemu_async>(&'life0 mut self, name: &'life1 str)
+                -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<
+                    Output = Result<Bound<'life0, Self::Transaction>, ()>
+                > + ::std::marker::Send + 'galemu_async> >
+                where 'life0: 'galemu_async, 'life1: 'galemu_async, Self: 'galemu_async;
+        };
+        assert!(expanded.contains(&expected.to_string()), "{}", expanded);
+    }
+
+    #[test]
+    fn arguments_are_moved_into_the_future() {
+        let expanded = expand(parse_quote! {
+            impl Connection for Conn {
+                async fn exec(&self, mut count: u32, (a, b): (u8, u8)) {}
+            }
+        });
+        assert!(expanded.contains(&quote!((&'life0 self, count: u32, __arg2: (u8, u8))).to_string()));
+        let moves = quote!(let mut count = count; let (a, b) = __arg2;);
+        assert!(expanded.contains(&moves.to_string()), "{}", expanded);
+    }
+
+    #[test]
+    fn only_send_can_be_opted_out() {
+        assert!(!parse_args(quote!(?Send)).unwrap());
+        assert!(parse_args(quote!()).unwrap());
+        assert!(parse_args(quote!(Sync)).is_err());
+    }
+}
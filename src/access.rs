@@ -0,0 +1,67 @@
+//! Marker types to distinguish read-only from read-write transactions at compile time.
+//!
+//! Wrapper types declare their access mode by implementing [`AccessMode`]. Generic
+//! code can then require [`Writable`] access for write operations, which makes using
+//! them with a read-only transaction a compile time error:
+//!
+//! ```compile_fail
+//! use galemu::access::{AccessMode, ReadOnly, Writable};
+//!
+//! struct ReadTransaction;
+//!
+//! impl AccessMode for ReadTransaction {
+//!     type Access = ReadOnly;
+//! }
+//!
+//! fn insert<T>(_trans: &T)
+//!     where T: AccessMode, T::Access: Writable
+//! {}
+//!
+//! // error: `ReadOnly` is not `Writable`
+//! insert(&ReadTransaction);
+//! ```
+//!
+//! The transactions of the [`traits`](crate::traits) module (and so all backend
+//! integrations) declare their access mode, see there for how it's used.
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Implemented by the access mode markers [`ReadOnly`] and [`ReadWrite`].
+///
+/// This trait is sealed and can not be implemented outside of this crate.
+pub trait Access: sealed::Sealed {
+    /// True if write operations are not allowed.
+    const READ_ONLY: bool;
+}
+
+/// Implemented by access modes which allow write operations (i.e. [`ReadWrite`]).
+pub trait Writable: Access {}
+
+/// Marker for read-only access.
+#[derive(Debug)]
+pub enum ReadOnly {}
+
+/// Marker for read-write access.
+#[derive(Debug)]
+pub enum ReadWrite {}
+
+impl sealed::Sealed for ReadOnly {}
+impl sealed::Sealed for ReadWrite {}
+
+impl Access for ReadOnly {
+    const READ_ONLY: bool = true;
+}
+
+impl Access for ReadWrite {
+    const READ_ONLY: bool = false;
+}
+
+impl Writable for ReadWrite {}
+
+/// Declares the access mode of a (wrapper) type.
+pub trait AccessMode {
+    /// Either [`ReadOnly`] or [`ReadWrite`].
+    type Access: Access;
+}
@@ -0,0 +1,310 @@
+//! Async connection/transaction traits built on [`Bound`].
+//!
+//! This are the async counterparts of the traits in the `traits` module, for async
+//! drivers whose transactions borrow the connection. `begin` returns a boxed future
+//! resolving to a `Bound` transaction and `commit`/`rollback` consume the `Bound`
+//! returning boxed futures, so the traits are object safe and don't need generic
+//! associated types. Implementations normally just wrap a `async move` block into
+//! `Box::pin`. Like in `traits` every transaction declares it's access mode and
+//! `begin` returns writable transactions.
+//!
+//! ```edition2021
+//! use galemu::{Bound, create_gal_wrapper_type};
+//! use galemu::access::{AccessMode, ReadWrite};
+//! use galemu::async_traits::{AsyncConnection, AsyncTransaction, BoxFuture};
+//! # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+//! #     use std::{sync::Arc, task::{Context, Poll, Wake}};
+//! #     struct Noop;
+//! #     impl Wake for Noop { fn wake(self: Arc<Self>) {} }
+//! #     let waker = Arc::new(Noop).into();
+//! #     match Box::pin(future).as_mut().poll(&mut Context::from_waker(&waker)) {
+//! #         Poll::Ready(out) => out,
+//! #         Poll::Pending => unreachable!()
+//! #     }
+//! # }
+//!
+//! struct Connection { committed: usize }
+//! struct Transaction<'conn> { conn: &'conn mut Connection }
+//!
+//! create_gal_wrapper_type! { struct TransactionWrap(Transaction<'a>); }
+//!
+//! impl AsyncConnection for Connection {
+//!     type Error = String;
+//!     type Transaction = TransactionWrap;
+//!
+//!     fn begin(&mut self) -> BoxFuture<'_, Result<Bound<'_, TransactionWrap>, String>> {
+//!         Box::pin(async move { Ok(TransactionWrap::new(Transaction { conn: self })) })
+//!     }
+//! }
+//!
+//! impl AccessMode for TransactionWrap {
+//!     type Access = ReadWrite;
+//! }
+//!
+//! impl AsyncTransaction for TransactionWrap {
+//!     type Error = String;
+//!
+//!     fn commit(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), String>> {
+//!         Box::pin(async move {
+//!             TransactionWrap::into_inner(me).conn.committed += 1;
+//!             Ok(())
+//!         })
+//!     }
+//!
+//!     fn rollback(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), String>> {
+//!         Box::pin(async move {
+//!             drop(me);
+//!             Ok(())
+//!         })
+//!     }
+//! }
+//!
+//! async fn commit_once<C: AsyncConnection>(conn: &mut C) -> Result<(), C::Error> {
+//!     let trans = conn.begin().await?;
+//!     AsyncTransaction::commit(trans).await
+//! }
+//!
+//! let mut conn = Connection { committed: 0 };
+//! block_on(commit_once(&mut conn)).unwrap();
+//! assert_eq!(conn.committed, 1);
+//! ```
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll}
+};
+
+use {Bound, BoundExt, gal_blanket_impls};
+use access::{AccessMode, ReadWrite};
+use sealed;
+
+/// A boxed `Send` future, as returned by the traits in this module.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A async connection (or similar) which can begin transactions.
+///
+/// The connection has to be `Send` as the future returned by `begin` borrows it.
+pub trait AsyncConnection: Send {
+    /// The error returned by the connection and it's transactions.
+    type Error;
+    /// The (lifetime erased) transaction type.
+    ///
+    /// See `traits::Connection::Transaction` for why the `'static` bound is needed.
+    type Transaction: AsyncTransaction<Error=Self::Error, Access=ReadWrite> + 'static;
+
+    /// Begins a new (writable) transaction bound to the connection.
+    fn begin(&mut self) -> BoxFuture<'_, Result<Bound<'_, Self::Transaction>, Self::Error>>;
+}
+
+/// A async transaction which can be committed or rolled back.
+///
+/// Dropping a transaction without committing it should roll it back, as far as
+/// this is possible without awaiting anything.
+pub trait AsyncTransaction: for<'a> BoundExt<'a> + AccessMode {
+    /// The error returned by the transaction.
+    type Error;
+
+    /// Commits the transaction.
+    fn commit(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), Self::Error>>;
+
+    /// Rolls back the transaction.
+    fn rollback(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), Self::Error>>;
+}
+
+gal_blanket_impls! {
+    impl AsyncConnection {
+        type Error;
+        type Transaction;
+        fn begin(&mut self) -> BoxFuture<'_, Result<Bound<'_, Self::Transaction>, Self::Error>>;
+    }
+}
+
+/// Extension methods for all [`AsyncConnection`]s, like `traits::Connection2`.
+///
+/// It can't be implemented outside of this crate, so methods can be added to it
+/// without breaking other crates.
+pub trait AsyncConnection2: AsyncConnection + sealed::AsyncConnection {
+
+    /// Checks that the connection works by beginning a transaction and rolling it back.
+    fn check(&mut self) -> Check<'_, Self::Transaction, Self::Error> {
+        Check { begin: Some(self.begin()), rollback: None }
+    }
+}
+
+impl<C: ?Sized + AsyncConnection> AsyncConnection2 for C {}
+
+/// Extension functions for all [`AsyncTransaction`]s, like `traits::Transaction2`.
+///
+/// It can't be implemented outside of this crate, so functions can be added to it
+/// without breaking other crates.
+pub trait AsyncTransaction2: AsyncTransaction + sealed::AsyncTransaction {
+
+    /// Commits the transaction if `res` is `Ok` and rolls it back otherwise.
+    ///
+    /// If committing fails that error is returned. If rolling back fails the error
+    /// in `res` is returned anyway, as it's the cause of the rollback.
+    fn finish<R, E>(me: Bound<'_, Self>, res: Result<R, E>) -> Finish<'_, R, E, Self::Error>
+        where E: From<Self::Error>
+    {
+        let end = if res.is_ok() {
+            AsyncTransaction::commit(me)
+        } else {
+            AsyncTransaction::rollback(me)
+        };
+        Finish { end, res: Some(res) }
+    }
+}
+
+impl<T: AsyncTransaction> AsyncTransaction2 for T {}
+
+/// Future returned by [`AsyncConnection2::check()`].
+pub struct Check<'a, T: BoundExt<'a>, E> {
+    begin: Option<BoxFuture<'a, Result<Bound<'a, T>, E>>>,
+    rollback: Option<BoxFuture<'a, Result<(), E>>>
+}
+
+impl<'a, T, E> Future for Check<'a, T, E>
+    where T: AsyncTransaction<Error=E>
+{
+    type Output = Result<(), E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(mut begin) = self.begin.take() {
+            let trans = match begin.as_mut().poll(cx) {
+                Poll::Ready(Ok(trans)) => trans,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => {
+                    self.begin = Some(begin);
+                    return Poll::Pending;
+                }
+            };
+            self.rollback = Some(AsyncTransaction::rollback(trans));
+        }
+        self.rollback.as_mut().expect("Check polled after completion").as_mut().poll(cx)
+    }
+}
+
+/// Future returned by [`AsyncTransaction2::finish()`].
+pub struct Finish<'a, R, E, TE> {
+    end: BoxFuture<'a, Result<(), TE>>,
+    // the result if the transaction is committed successfully or rolled back
+    res: Option<Result<R, E>>
+}
+
+// `res` is never pinned
+impl<'a, R, E, TE> Unpin for Finish<'a, R, E, TE> {}
+
+impl<'a, R, E, TE> Future for Finish<'a, R, E, TE>
+    where E: From<TE>
+{
+    type Output = Result<R, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let end = match self.end.as_mut().poll(cx) {
+            Poll::Ready(end) => end,
+            Poll::Pending => return Poll::Pending
+        };
+        let res = self.res.take().expect("Finish polled after completion");
+        Poll::Ready(match (res, end) {
+            (Ok(_), Err(err)) => Err(E::from(err)),
+            (res, _) => res
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{
+        future,
+        sync::Arc,
+        task::{Context, Poll, Wake}
+    };
+    use create_gal_wrapper_type;
+    use super::*;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Noop;
+
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Arc::new(Noop).into();
+        match Box::pin(future).as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(out) => out,
+            Poll::Pending => unreachable!()
+        }
+    }
+
+    struct Store {
+        value: u32
+    }
+
+    /// Stages a new value which is written to `target` on commit.
+    struct Trans<'a> {
+        target: &'a mut u32,
+        value: u32
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+
+    impl AsyncConnection for Store {
+        type Error = ();
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> BoxFuture<'_, Result<Bound<'_, TransWrap>, ()>> {
+            let trans = TransWrap::new(Trans { value: self.value + 1, target: &mut self.value });
+            Box::pin(future::ready(Ok(trans)))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl AsyncTransaction for TransWrap {
+        type Error = ();
+
+        fn commit(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), ()>> {
+            let trans = TransWrap::into_inner(me);
+            *trans.target = trans.value;
+            Box::pin(future::ready(Ok(())))
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), ()>> {
+            drop(me);
+            Box::pin(future::ready(Ok(())))
+        }
+    }
+
+    fn begin_commit_and_rollback<C: AsyncConnection>(mut conn: C) -> Result<(), C::Error> {
+        let trans = block_on(conn.begin())?;
+        block_on(AsyncTransaction::commit(trans))?;
+        let trans = block_on(conn.begin())?;
+        block_on(AsyncTransaction::rollback(trans))
+    }
+
+    #[test]
+    fn extension_traits_are_implemented_for_all_connections() {
+        let mut store = Store { value: 1 };
+        assert_eq!(block_on(store.check()), Ok(()));
+        assert_eq!(store.value, 1);
+        let trans = block_on(store.begin()).unwrap();
+        assert_eq!(block_on(AsyncTransaction2::finish(trans, Err::<(), _>(()))), Err(()));
+        assert_eq!(store.value, 1);
+        let trans = block_on(store.begin()).unwrap();
+        assert_eq!(block_on(AsyncTransaction2::finish(trans, Ok::<_, ()>("committed"))), Ok("committed"));
+        assert_eq!(store.value, 2);
+    }
+
+    #[test]
+    fn generic_code_can_use_the_traits() {
+        let mut store = Store { value: 1 };
+        begin_commit_and_rollback(&mut store).unwrap();
+        assert_eq!(store.value, 2);
+        let mut boxed: Box<dyn AsyncConnection<Error=(), Transaction=TransWrap>> = Box::new(store);
+        begin_commit_and_rollback(&mut boxed).unwrap();
+        begin_commit_and_rollback(boxed).unwrap();
+    }
+}
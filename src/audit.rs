@@ -0,0 +1,144 @@
+//! Auditing of the unsafe code run through [`unsafe_block`](crate::unsafe_block).
+//!
+//! In builds with debug assertions every executed `unsafe_block!` (incl. the ones in
+//! wrappers generated by [`create_gal_wrapper_type`](crate::create_gal_wrapper_type))
+//! reports it's reason and location to the hook set with [`set_hook()`], e.g. to
+//! collect which unsafe code a test suite actually exercises:
+//!
+//! ```
+//! # #[macro_use] extern crate galemu;
+//! use std::sync::Mutex;
+//! use galemu::audit::{self, UnsafeUsage};
+//!
+//! static REASONS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+//!
+//! # fn main() {
+//! audit::set_hook(|usage: &UnsafeUsage| REASONS.lock().unwrap().push(usage.reason));
+//! let value = unsafe_block!{ "the index is in bounds" => { *[12].get_unchecked(0) } };
+//! assert_eq!(value, 12);
+//! # if cfg!(debug_assertions) {
+//! assert!(REASONS.lock().unwrap().contains(&"the index is in bounds"));
+//! # }
+//! # }
+//! ```
+use std::{
+    cell::Cell,
+    sync::RwLock
+};
+
+/// The reason and location of a executed unsafe block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsafeUsage {
+    /// The reason given to `unsafe_block!`.
+    pub reason: &'static str,
+    /// The file of the (outermost) macro invocation.
+    pub file: &'static str,
+    /// The line of the (outermost) macro invocation.
+    pub line: u32,
+    /// The column of the (outermost) macro invocation.
+    pub column: u32
+}
+
+type Hook = Box<dyn Fn(&UnsafeUsage) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+thread_local! {
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sets the hook called for every executed unsafe block, replacing the previous one.
+///
+/// Unsafe blocks run by the hook itself are not reported.
+pub fn set_hook<F>(hook: F)
+    where F: Fn(&UnsafeUsage) + Send + Sync + 'static
+{
+    *HOOK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(hook));
+}
+
+/// Removes the hook, returning it if there was one.
+pub fn take_hook() -> Option<Hook> {
+    HOOK.write().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+}
+
+/// Resets `IN_HOOK` when dropped, even if the hook panics.
+struct InHookGuard;
+
+impl Drop for InHookGuard {
+    fn drop(&mut self) {
+        IN_HOOK.with(|in_hook| in_hook.set(false));
+    }
+}
+
+#[doc(hidden)]
+pub fn record(usage: &UnsafeUsage) {
+    if IN_HOOK.with(|in_hook| in_hook.replace(true)) {
+        return;
+    }
+    let _guard = InHookGuard;
+    if let Some(hook) = &*HOOK.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+        hook(usage);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{
+        panic,
+        sync::{Arc, Mutex},
+        thread
+    };
+    use super::*;
+
+    // the hook is global, so tests setting it must not run concurrently
+    static HOOK_TESTS: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn executed_unsafe_blocks_are_reported() {
+        let _lock = HOOK_TESTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let usages = Arc::new(Mutex::new(Vec::new()));
+        let recorded = usages.clone();
+        set_hook(move |usage| {
+            // not reported again
+            let _ = unsafe_block!{ "valid utf-8" => { ::std::str::from_utf8_unchecked(b"hook") } };
+            recorded.lock().unwrap().push(*usage)
+        });
+        let line = line!() + 1;
+        let text = unsafe_block!{ "valid utf-8 as well" => { ::std::str::from_utf8_unchecked(b"test") } };
+        take_hook();
+
+        let usages = usages.lock().unwrap();
+        assert_eq!(text, "test");
+        assert!(!usages.iter().any(|usage| usage.reason == "valid utf-8"));
+        let usage = usages.iter().find(|usage| usage.reason == "valid utf-8 as well");
+        if cfg!(debug_assertions) {
+            assert_eq!(usage.map(|usage| (usage.file, usage.line)), Some((file!(), line)));
+        } else {
+            assert!(usage.is_none());
+        }
+    }
+    #[test]
+    fn panicking_hooks_do_not_disable_auditing() {
+        let _lock = HOOK_TESTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let usages = Arc::new(Mutex::new(Vec::new()));
+        let recorded = usages.clone();
+        let test_thread = thread::current().id();
+        set_hook(move |usage| {
+            if thread::current().id() == test_thread {
+                if usage.reason == "hook panics" {
+                    panic!("hook failed");
+                }
+                recorded.lock().unwrap().push(usage.reason);
+            }
+        });
+        let res = panic::catch_unwind(|| unsafe_block!{ "hook panics" => { ::std::str::from_utf8_unchecked(b"a") } });
+        let _ = unsafe_block!{ "reported again" => { ::std::str::from_utf8_unchecked(b"b") } };
+        take_hook();
+
+        assert_eq!(res.is_err(), cfg!(debug_assertions));
+        if cfg!(debug_assertions) {
+            assert_eq!(*usages.lock().unwrap(), ["reported again"]);
+        }
+    }
+}
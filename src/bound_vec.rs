@@ -0,0 +1,203 @@
+use std::{
+    iter::FromIterator,
+    mem,
+    slice, vec
+};
+
+use {Bound, BoundExt};
+
+/// A collection of values which are all bound to the same lifetime `'a`.
+///
+/// E.g. this can be used to store multiple prepared statements created from the
+/// same connection borrow.
+///
+/// # Drop
+///
+/// Like local variables the elements are dropped in reverse order of insertion,
+/// i.e. the element pushed last is dropped (incl. [`BoundExt::pre_drop()`]) first.
+pub struct BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    items: Vec<Bound<'a, T>>
+}
+
+impl<'a, T> BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+
+    /// Creates a new empty `BoundVec`.
+    pub fn new() -> Self {
+        BoundVec { items: Vec::new() }
+    }
+
+    /// Creates a new empty `BoundVec` with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        BoundVec { items: Vec::with_capacity(capacity) }
+    }
+
+    /// Appends a bound value to the end of the collection.
+    pub fn push(&mut self, value: Bound<'a, T>) {
+        self.items.push(value)
+    }
+
+    /// Removes the last bound value and returns it.
+    pub fn pop(&mut self) -> Option<Bound<'a, T>> {
+        self.items.pop()
+    }
+
+    /// Returns a reference to the bound value at the given index.
+    pub fn get(&self, idx: usize) -> Option<&Bound<'a, T>> {
+        self.items.get(idx)
+    }
+
+    /// Returns a mutable reference to the bound value at the given index.
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Bound<'a, T>> {
+        self.items.get_mut(idx)
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterates over references to the bound values in insertion order.
+    pub fn iter(&self) -> slice::Iter<'_, Bound<'a, T>> {
+        self.items.iter()
+    }
+
+    /// Iterates over mutable references to the bound values in insertion order.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Bound<'a, T>> {
+        self.items.iter_mut()
+    }
+}
+
+impl<'a, T> Default for BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    fn default() -> Self {
+        BoundVec::new()
+    }
+}
+
+impl<'a, T> Drop for BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    fn drop(&mut self) {
+        while let Some(item) = self.items.pop() {
+            drop(item);
+        }
+    }
+}
+
+impl<'a, T> FromIterator<Bound<'a, T>> for BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    fn from_iter<I>(iter: I) -> Self
+        where I: IntoIterator<Item=Bound<'a, T>>
+    {
+        BoundVec { items: iter.into_iter().collect() }
+    }
+}
+
+impl<'a, T> Extend<Bound<'a, T>> for BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=Bound<'a, T>>
+    {
+        self.items.extend(iter)
+    }
+}
+
+impl<'a, T> IntoIterator for BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    type Item = Bound<'a, T>;
+    type IntoIter = vec::IntoIter<Bound<'a, T>>;
+
+    /// Iterates over the bound values in insertion order.
+    ///
+    /// Note that values not consumed by the iterator are dropped in insertion order.
+    fn into_iter(mut self) -> Self::IntoIter {
+        mem::take(&mut self.items).into_iter()
+    }
+}
+
+impl<'b, 'a, T> IntoIterator for &'b BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    type Item = &'b Bound<'a, T>;
+    type IntoIter = slice::Iter<'b, Bound<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'b, 'a, T> IntoIterator for &'b mut BoundVec<'a, T>
+    where T: BoundExt<'a>
+{
+    type Item = &'b mut Bound<'a, T>;
+    type IntoIter = slice::IterMut<'b, Bound<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use create_gal_wrapper_type;
+    use super::*;
+
+    struct Statement<'conn> {
+        id: usize,
+        log: &'conn RefCell<Vec<usize>>
+    }
+
+    impl<'conn> Drop for Statement<'conn> {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(self.id);
+        }
+    }
+
+    create_gal_wrapper_type!{ struct StmtWrap(Statement<'a>); }
+
+    #[test]
+    fn elements_are_dropped_in_reverse_order() {
+        let log = RefCell::new(Vec::new());
+        {
+            let stmts = (0..3)
+                .map(|id| StmtWrap::new(Statement { id, log: &log }))
+                .collect::<BoundVec<_>>();
+            assert_eq!(stmts.len(), 3);
+            let ids = stmts.iter().map(|stmt| StmtWrap::get(stmt).id).collect::<Vec<_>>();
+            assert_eq!(ids, &[0, 1, 2]);
+        }
+        assert_eq!(*log.borrow(), &[2, 1, 0]);
+    }
+
+    #[test]
+    fn elements_can_be_accessed() {
+        let log = RefCell::new(Vec::new());
+        let mut stmts = BoundVec::new();
+        assert!(stmts.is_empty());
+        stmts.push(StmtWrap::new(Statement { id: 1, log: &log }));
+        stmts.push(StmtWrap::new(Statement { id: 2, log: &log }));
+
+        StmtWrap::get_mut(stmts.get_mut(0).unwrap()).id = 10;
+        assert_eq!(StmtWrap::get(stmts.get(0).unwrap()).id, 10);
+        assert!(stmts.get(2).is_none());
+
+        let last = stmts.pop().unwrap();
+        assert_eq!(StmtWrap::into_inner(last).id, 2);
+        assert_eq!(*log.borrow(), &[2]);
+    }
+}
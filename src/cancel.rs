@@ -0,0 +1,452 @@
+//! Cancelling bound transactions with a `CancellationToken` (requires the `tokio_util` feature).
+//!
+//! [`Cancellable`] wraps a bound transaction and a `tokio_util::sync::CancellationToken`.
+//! Every step run through it first checks the token, once it's cancelled no further
+//! steps are run and the transaction is explicitly rolled back (instead of just being
+//! dropped) when it's finished. So graceful shutdown paths can cancel the token and
+//! wait for the live transactions to be rolled back. [`scope()`] and [`run()`] do this
+//! for a closure running the steps:
+//!
+//! ```
+//! # extern crate galemu;
+//! # extern crate tokio_util;
+//! use galemu::{Bound, create_gal_wrapper_type};
+//! use galemu::access::{AccessMode, ReadWrite};
+//! use galemu::cancel::{self, CancelError};
+//! use galemu::traits::{Connection, Transaction};
+//! use tokio_util::sync::CancellationToken;
+//!
+//! struct Store { log: Vec<&'static str> }
+//! struct Trans<'a> { store: &'a mut Store }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+//!
+//! impl Connection for Store {
+//!     type Error = ();
+//!     type Transaction = TransWrap;
+//!
+//!     fn begin(&mut self) -> Result<Bound<'_, TransWrap>, ()> {
+//!         Ok(TransWrap::new(Trans { store: self }))
+//!     }
+//! }
+//!
+//! impl AccessMode for TransWrap {
+//!     type Access = ReadWrite;
+//! }
+//!
+//! impl Transaction for TransWrap {
+//!     type Error = ();
+//!
+//!     fn commit(me: Bound<'_, Self>) -> Result<(), ()> {
+//!         TransWrap::into_inner(me).store.log.push("commit");
+//!         Ok(())
+//!     }
+//!
+//!     fn rollback(me: Bound<'_, Self>) -> Result<(), ()> {
+//!         TransWrap::into_inner(me).store.log.push("rollback");
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mut store = Store { log: Vec::new() };
+//! let token = CancellationToken::new();
+//! let res = cancel::run(&mut store, &token, |trans| {
+//!     trans.step(|trans| Ok::<_, ()>(TransWrap::get_mut(trans).store.log.push("first")))?;
+//!     token.cancel();
+//!     trans.step(|trans| Ok(TransWrap::get_mut(trans).store.log.push("second")))
+//! });
+//! assert_eq!(res, Err(CancelError::Cancelled));
+//! assert_eq!(store.log, &["first", "rollback"]);
+//! # }
+//! ```
+//!
+//! For the `AsyncTransaction`s of the `async_traits` module (which requires the
+//! `async_traits` feature, too) the steps are run with `Cancellable::step_async` and
+//! the transaction is ended with `Cancellable::finish_async`. As a future running a
+//! closure with a borrow of the transaction it owns would be self-referential, there
+//! is no async `scope`/`run`, async code keeps the `Cancellable` in a local variable
+//! instead:
+//!
+//! ```ignore
+//! let mut trans = Cancellable::new(conn.begin().await?, &token);
+//! let res = trans.step_async(|trans| insert(trans, 1)).await;
+//! trans.finish_async(res).await
+//! ```
+use std::{
+    error::Error,
+    fmt::{self, Display}
+};
+#[cfg(feature = "async_traits")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll}
+};
+
+use tokio_util::sync::CancellationToken;
+
+use {Bound, BoundExt};
+#[cfg(feature = "async_traits")]
+use async_traits::{AsyncTransaction, BoxFuture};
+use traits::{Connection, Transaction};
+
+/// Error of a step (or the whole transaction) run through a [`Cancellable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelError<E> {
+    /// The token was cancelled, the step wasn't run.
+    Cancelled,
+    /// The step (or beginning or ending the transaction) failed.
+    Failed(E)
+}
+
+impl<E: Display> Display for CancelError<E> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CancelError::Cancelled => fter.write_str("cancelled"),
+            CancelError::Failed(ref err) => Display::fmt(err, fter)
+        }
+    }
+}
+
+impl<E: Error> Error for CancelError<E> {}
+
+/// A bound transaction whose steps are only run as long as a token isn't cancelled.
+pub struct Cancellable<'t, 'a, T: BoundExt<'a>> {
+    trans: Bound<'a, T>,
+    token: &'t CancellationToken
+}
+
+impl<'t, 'a, T> Cancellable<'t, 'a, T>
+    where T: BoundExt<'a>
+{
+
+    /// Wraps the transaction.
+    pub fn new(trans: Bound<'a, T>, token: &'t CancellationToken) -> Self {
+        Cancellable { trans, token }
+    }
+
+    /// Returns true if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Runs `f` with the transaction, if the token isn't cancelled.
+    pub fn step<R, E, F>(&mut self, f: F) -> Result<R, CancelError<E>>
+        where F: FnOnce(&mut Bound<'a, T>) -> Result<R, E>
+    {
+        if self.is_cancelled() {
+            return Err(CancelError::Cancelled);
+        }
+        f(&mut self.trans).map_err(CancelError::Failed)
+    }
+
+    /// Returns the transaction, without checking the token.
+    pub fn into_inner(self) -> Bound<'a, T> {
+        self.trans
+    }
+}
+
+impl<'t, 'a, T> Cancellable<'t, 'a, T>
+    where T: Transaction
+{
+
+    /// Ends the transaction depending on `res`, the result of it's steps.
+    ///
+    /// The transaction is committed if `res` is `Ok` and the token isn't cancelled,
+    /// else it's rolled back and the error of `res` (or [`CancelError::Cancelled`])
+    /// is returned, even if rolling back fails.
+    pub fn finish<R, E>(self, res: Result<R, CancelError<E>>) -> Result<R, CancelError<E>>
+        where E: From<T::Error>
+    {
+        match res {
+            Ok(value) if !self.is_cancelled() => {
+                Transaction::commit(self.trans).map_err(|err| CancelError::Failed(E::from(err)))?;
+                Ok(value)
+            },
+            res => {
+                let _ = Transaction::rollback(self.trans);
+                res.and(Err(CancelError::Cancelled))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async_traits")]
+impl<'t, 'a, T> Cancellable<'t, 'a, T>
+    where T: AsyncTransaction
+{
+
+    /// Runs the future returned by `f` with the transaction, if the token isn't cancelled.
+    ///
+    /// The token is checked when this is called, not when the future is polled.
+    pub fn step_async<'s, R, E, F, Fut>(&'s mut self, f: F) -> Step<Fut>
+        where F: FnOnce(&'s mut Bound<'a, T>) -> Fut, Fut: Future<Output=Result<R, E>>
+    {
+        if self.is_cancelled() {
+            return Step { fut: None };
+        }
+        Step { fut: Some(Box::pin(f(&mut self.trans))) }
+    }
+
+    /// Ends the transaction depending on `res`, see [`Cancellable::finish()`].
+    pub fn finish_async<R, E>(self, res: Result<R, CancelError<E>>) -> Finish<'a, R, E, T::Error>
+        where E: From<T::Error>
+    {
+        let commit = res.is_ok() && !self.is_cancelled();
+        let end = if commit {
+            AsyncTransaction::commit(self.trans)
+        } else {
+            AsyncTransaction::rollback(self.trans)
+        };
+        Finish { end, res: Some(res.and_then(|value| if commit { Ok(value) } else { Err(CancelError::Cancelled) })) }
+    }
+}
+
+/// Future returned by [`Cancellable::step_async()`].
+#[cfg(feature = "async_traits")]
+pub struct Step<Fut> {
+    // `None` if the token was cancelled
+    fut: Option<Pin<Box<Fut>>>
+}
+
+#[cfg(feature = "async_traits")]
+impl<R, E, Fut> Future for Step<Fut>
+    where Fut: Future<Output=Result<R, E>>
+{
+    type Output = Result<R, CancelError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match self.fut {
+            Some(ref mut fut) => fut.as_mut().poll(cx).map(|res| res.map_err(CancelError::Failed)),
+            None => Poll::Ready(Err(CancelError::Cancelled))
+        }
+    }
+}
+
+/// Future returned by [`Cancellable::finish_async()`].
+#[cfg(feature = "async_traits")]
+pub struct Finish<'a, R, E, TE> {
+    end: BoxFuture<'a, Result<(), TE>>,
+    // the result if the transaction is committed successfully or rolled back
+    res: Option<Result<R, CancelError<E>>>
+}
+
+// `res` is never pinned
+#[cfg(feature = "async_traits")]
+impl<'a, R, E, TE> Unpin for Finish<'a, R, E, TE> {}
+
+#[cfg(feature = "async_traits")]
+impl<'a, R, E, TE> Future for Finish<'a, R, E, TE>
+    where E: From<TE>
+{
+    type Output = Result<R, CancelError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let end = match self.end.as_mut().poll(cx) {
+            Poll::Ready(end) => end,
+            Poll::Pending => return Poll::Pending
+        };
+        let res = self.res.take().expect("Finish polled after completion");
+        Poll::Ready(match (res, end) {
+            (Ok(_), Err(err)) => Err(CancelError::Failed(E::from(err))),
+            (res, _) => res
+        })
+    }
+}
+
+/// Runs `f` with `trans` wrapped into a [`Cancellable`] and finishes it with the result.
+///
+/// See [`Cancellable::finish()`] for when the transaction is committed.
+pub fn scope<'a, T, R, E, F>(trans: Bound<'a, T>, token: &CancellationToken, f: F) -> Result<R, CancelError<E>>
+    where T: Transaction, E: From<T::Error>,
+          F: FnOnce(&mut Cancellable<'_, 'a, T>) -> Result<R, CancelError<E>>
+{
+    let mut trans = Cancellable::new(trans, token);
+    let res = f(&mut trans);
+    trans.finish(res)
+}
+
+/// Begins a transaction on `conn` (if the token isn't cancelled) and runs [`scope()`] with it.
+pub fn run<C, R, E, F>(conn: &mut C, token: &CancellationToken, f: F) -> Result<R, CancelError<E>>
+    where C: Connection, E: From<C::Error>,
+          F: for<'a> FnOnce(&mut Cancellable<'_, 'a, C::Transaction>) -> Result<R, CancelError<E>>
+{
+    if token.is_cancelled() {
+        return Err(CancelError::Cancelled);
+    }
+    let trans = conn.begin().map_err(|err| CancelError::Failed(E::from(err)))?;
+    scope(trans, token, f)
+}
+
+
+#[cfg(test)]
+mod test {
+    use create_gal_wrapper_type;
+    use access::{AccessMode, ReadWrite};
+    use super::*;
+
+    /// Logs the ends of it's transactions, fails commits if `fail_commits` is set.
+    struct Store {
+        log: Vec<&'static str>,
+        fail_commits: bool
+    }
+
+    struct Trans<'a> {
+        store: &'a mut Store
+    }
+
+    create_gal_wrapper_type!{
+        struct TransWrap(Trans<'a>);
+        on_drop = |trans| trans.store.log.push("drop");
+    }
+
+    impl Connection for Store {
+        type Error = &'static str;
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> Result<Bound<'_, TransWrap>, &'static str> {
+            Ok(TransWrap::new(Trans { store: self }))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl Transaction for TransWrap {
+        type Error = &'static str;
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            let trans = TransWrap::into_inner(me);
+            if trans.store.fail_commits {
+                return Err("commit failed");
+            }
+            trans.store.log.push("commit");
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            TransWrap::into_inner(me).store.log.push("rollback");
+            Ok(())
+        }
+    }
+
+    fn push(trans: &mut Bound<TransWrap>, entry: &'static str) -> Result<(), &'static str> {
+        TransWrap::get_mut(trans).store.log.push(entry);
+        Ok(())
+    }
+
+    #[test]
+    fn transactions_are_committed_if_not_cancelled() {
+        let mut store = Store { log: Vec::new(), fail_commits: false };
+        let token = CancellationToken::new();
+        let res = run(&mut store, &token, |trans| {
+            trans.step(|trans| push(trans, "first"))?;
+            trans.step(|trans| push(trans, "second"))
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(store.log, &["first", "second", "commit"]);
+
+        store.log.clear();
+        store.fail_commits = true;
+        let res = run(&mut store, &token, |trans| trans.step(|trans| push(trans, "first")));
+        assert_eq!(res, Err(CancelError::Failed("commit failed")));
+        assert_eq!(store.log, &["first"]);
+    }
+
+    #[test]
+    fn cancelled_transactions_are_rolled_back() {
+        let mut store = Store { log: Vec::new(), fail_commits: false };
+        let token = CancellationToken::new();
+        let res = run(&mut store, &token, |trans| {
+            trans.step(|trans| push(trans, "first"))?;
+            token.cancel();
+            assert!(trans.is_cancelled());
+            trans.step(|trans| push(trans, "second"))
+        });
+        assert_eq!(res, Err(CancelError::Cancelled));
+        assert_eq!(store.log, &["first", "rollback"]);
+
+        // cancelled after the last step
+        let token = CancellationToken::new();
+        let res = scope(store.begin().unwrap(), &token, |trans| {
+            trans.step(|trans| push(trans, "third"))?;
+            token.cancel();
+            Ok::<_, CancelError<&'static str>>(())
+        });
+        assert_eq!(res, Err(CancelError::Cancelled));
+        assert_eq!(run(&mut store, &token, |_| Ok::<_, CancelError<&'static str>>(())), Err(CancelError::Cancelled));
+        assert_eq!(store.log, &["first", "rollback", "third", "rollback"]);
+    }
+
+    #[test]
+    fn failed_steps_roll_back() {
+        let mut store = Store { log: Vec::new(), fail_commits: false };
+        let token = CancellationToken::new();
+        let res = run(&mut store, &token, |trans| {
+            trans.step(|trans| push(trans, "first"))?;
+            trans.step(|_| Err("step failed"))
+        });
+        assert_eq!(res, Err::<(), _>(CancelError::Failed("step failed")));
+        assert_eq!(store.log, &["first", "rollback"]);
+    }
+
+    #[cfg(feature = "async_traits")]
+    mod async_traits {
+        use std::{future, sync::Arc, task::Wake};
+        use async_traits::AsyncTransaction;
+        use super::*;
+
+        fn block_on<F: Future>(future: F) -> F::Output {
+            struct Noop;
+
+            impl Wake for Noop {
+                fn wake(self: Arc<Self>) {}
+            }
+
+            let waker = Arc::new(Noop).into();
+            match Box::pin(future).as_mut().poll(&mut Context::from_waker(&waker)) {
+                Poll::Ready(out) => out,
+                Poll::Pending => unreachable!()
+            }
+        }
+
+        impl AsyncTransaction for TransWrap {
+            type Error = &'static str;
+
+            fn commit(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), &'static str>> {
+                Box::pin(future::ready(Transaction::commit(me)))
+            }
+
+            fn rollback(me: Bound<'_, Self>) -> BoxFuture<'_, Result<(), &'static str>> {
+                Box::pin(future::ready(Transaction::rollback(me)))
+            }
+        }
+
+        fn push_async(trans: &mut Bound<TransWrap>, entry: &'static str) -> future::Ready<Result<(), &'static str>> {
+            future::ready(push(trans, entry))
+        }
+
+        #[test]
+        fn async_transactions_can_be_cancelled() {
+            let mut store = Store { log: Vec::new(), fail_commits: false };
+            let token = CancellationToken::new();
+            {
+                let mut trans = Cancellable::new(store.begin().unwrap(), &token);
+                block_on(trans.step_async(|trans| push_async(trans, "first"))).unwrap();
+                let res = block_on(trans.step_async(|trans| push_async(trans, "second")));
+                assert_eq!(block_on(trans.finish_async(res)), Ok(()));
+            }
+            {
+                let mut trans = Cancellable::new(store.begin().unwrap(), &token);
+                block_on(trans.step_async(|trans| push_async(trans, "third"))).unwrap();
+                token.cancel();
+                let res = block_on(trans.step_async(|trans| push_async(trans, "fourth")));
+                assert_eq!(res, Err(CancelError::Cancelled));
+                assert_eq!(block_on(trans.finish_async(res)), Err(CancelError::Cancelled));
+            }
+            assert_eq!(store.log, &["first", "second", "commit", "third", "rollback"]);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! `const` variants of `Bound::new` and `Bound::_into_inner` (behind the `const_fn` feature).
+//!
+//! This needs Rust 1.71 (for `ptr::read` in `const fn`), which is why it's behind
+//! a feature. Tracing can't be done in a `const fn`, so neither function emits
+//! trace events. Validity checks are still done, see [`Bound::into_inner_const()`].
+use std::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr
+};
+
+use {Bound, BoundExt};
+#[cfg(all(feature = "validity_checks", debug_assertions))]
+use validity::Validity;
+
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+
+    /// `const` variant of [`Bound::new()`], which doesn't emit a trace event.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Bound::new()`].
+    #[allow(unsafe_code)]
+    pub const unsafe fn new_const(inner: T) -> Self {
+        Bound {
+            limiter: PhantomData,
+            #[cfg(all(feature = "validity_checks", debug_assertions))]
+            validity: Validity::Valid,
+            inner
+        }
+    }
+
+    /// `const` variant of [`Bound::_into_inner()`], which doesn't emit a trace event.
+    ///
+    /// With the `validity_checks` feature (in debug builds) this still panics if the
+    /// value was pre-dropped or moved out, but the panic message doesn't name the type.
+    #[allow(unsafe_code)]
+    pub const fn into_inner_const(me: Self) -> T {
+        // like `into_inner_untraced`, but `ManuallyDrop`s `Deref` can't be used in
+        // a `const fn` so the (transparent) `ManuallyDrop` is accessed through a pointer
+        let me = ManuallyDrop::new(me);
+        let me = &me as *const ManuallyDrop<Self> as *const Self;
+        // safe as `me` is never dropped so inner is only moved out once
+        unsafe {
+            #[cfg(all(feature = "validity_checks", debug_assertions))]
+            match (*me).validity {
+                Validity::Valid => (),
+                Validity::PreDropped => panic!("bound value used after `pre_drop` was run"),
+                Validity::Consumed => panic!("bound value used after it's inner value was moved out")
+            }
+            ptr::read(&(*me).inner)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use Static;
+    use super::*;
+
+    #[allow(unsafe_code)]
+    const BOUND: Bound<'static, Static<u32>> = unsafe { Bound::new_const(Static(3)) };
+
+    const VALUE: Static<u32> = Bound::into_inner_const(BOUND);
+
+    #[test]
+    fn bound_values_can_be_created_and_unwrapped_in_const_context() {
+        assert_eq!(VALUE, Static(3));
+        assert_eq!(BOUND.0, 3);
+        assert_eq!(Bound::into_inner_const(Bound::new_static(vec![4])), Static(vec![4]));
+    }
+}
@@ -0,0 +1,220 @@
+//! A cursor trait for iterating over rows bound to the cursor.
+//!
+//! Drivers normally expose cursors (or result sets) whose rows borrow from the
+//! cursor/statement, e.g. `Row<'stmt>`. As the row type depends on the lifetime
+//! of the borrow it can't be an associated type, but a `Bound` wrapper of it can.
+//!
+//! [`create_gal_row_type`](crate::create_gal_row_type) wraps a existing row type
+//! (using [`create_gal_wrapper_type`](crate::create_gal_wrapper_type)) and implements
+//! [`GeneralCursor`] for the cursor using it:
+//!
+//! ```
+//! # #[macro_use] extern crate galemu;
+//! use galemu::{Bound, cursor::GeneralCursor};
+//!
+//! struct Cursor { rows: Vec<String>, pos: usize }
+//! struct Row<'cursor> { value: &'cursor str }
+//!
+//! impl Cursor {
+//!     fn next(&mut self) -> Result<Option<Row>, ()> {
+//!         self.pos += 1;
+//!         Ok(self.rows.get(self.pos - 1).map(|value| Row { value }))
+//!     }
+//!
+//!     fn columns(&self) -> Vec<&str> {
+//!         vec!["value"]
+//!     }
+//! }
+//!
+//! create_gal_row_type! {
+//!     struct RowWrap(Row<'a>);
+//!
+//!     impl GeneralCursor for Cursor {
+//!         type Error = ();
+//!         next_row = Cursor::next;
+//!         columns = Cursor::columns;
+//!     }
+//! }
+//!
+//! // works with any cursor with rows wrapped by `RowWrap`
+//! fn concat<C: GeneralCursor<Row=RowWrap>>(cursor: &mut C) -> Result<String, C::Error> {
+//!     let mut out = String::new();
+//!     cursor.for_each_row(|row| out.push_str(RowWrap::get(&row).value))?;
+//!     Ok(out)
+//! }
+//!
+//! # fn main() {
+//! let mut cursor = Cursor { rows: vec!["a".to_owned(), "b".to_owned()], pos: 0 };
+//! assert_eq!(cursor.columns(), &["value"]);
+//! assert_eq!(concat(&mut cursor).unwrap(), "ab");
+//! # }
+//! ```
+use {Bound, BoundExt};
+use sealed;
+
+/// A cursor (or result set) over rows which borrow from the cursor.
+pub trait GeneralCursor {
+    /// The (lifetime erased) row type.
+    type Row: for<'a> BoundExt<'a>;
+    /// The error returned when fetching rows.
+    type Error;
+
+    /// Returns the names of the columns of the rows.
+    fn columns(&self) -> Vec<&str>;
+
+    /// Fetches the next row, returns `None` if there are no more rows.
+    fn next_row(&mut self) -> Result<Option<Bound<'_, Self::Row>>, Self::Error>;
+
+    /// Calls the closure with all remaining rows.
+    ///
+    /// This is a replacement for a `Iterator` implementation, which isn't
+    /// possible as the rows borrow from the cursor.
+    fn for_each_row<F>(&mut self, mut func: F) -> Result<(), Self::Error>
+        where F: FnMut(Bound<'_, Self::Row>)
+    {
+        while let Some(row) = self.next_row()? {
+            func(row);
+        }
+        Ok(())
+    }
+}
+
+/// Extension methods for all [`GeneralCursor`]s.
+///
+/// It can't be implemented outside of this crate, so methods can be added to it
+/// without breaking other crates, see [Extending the traits](crate::traits#extending-the-traits).
+pub trait GeneralCursor2: GeneralCursor + sealed::GeneralCursor {
+
+    /// Calls the fallible closure with all remaining rows, stopping at the first error.
+    fn try_for_each_row<E, F>(&mut self, mut func: F) -> Result<(), E>
+        where E: From<Self::Error>,
+              F: FnMut(Bound<'_, Self::Row>) -> Result<(), E>
+    {
+        while let Some(row) = self.next_row()? {
+            func(row)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: ?Sized + GeneralCursor> GeneralCursor2 for C {}
+
+/// Wraps a existing row type and implements [`GeneralCursor`](crate::cursor::GeneralCursor)
+/// using it.
+///
+/// The row wrapper is created with [`create_gal_wrapper_type`](crate::create_gal_wrapper_type),
+/// the cursor has to provide a function returning `Result<Option<Row<'_>>, Error>` for
+/// `next_row` and one returning `Vec<&str>` for `columns`, both taking the cursor as first
+/// argument.
+///
+/// See the [`cursor`](crate::cursor) module for an example.
+#[macro_export]
+macro_rules! create_gal_row_type {
+    (
+        $(#[$attr:meta])*
+        $v:vis struct $Wrap:ident($Row:ident<$lt:tt>);
+
+        impl $(<$($gen:lifetime),*>)* GeneralCursor for $Cursor:ty {
+            type Error = $Error:ty;
+            next_row = $next_row:expr;
+            columns = $columns:expr;
+        }
+    ) => (
+        $crate::create_gal_wrapper_type! {
+            $(#[$attr])*
+            $v struct $Wrap($Row<$lt>);
+        }
+
+        impl $(<$($gen),*>)* $crate::cursor::GeneralCursor for $Cursor {
+            type Row = $Wrap;
+            type Error = $Error;
+
+            fn columns(&self) -> Vec<&str> {
+                ($columns)(self)
+            }
+
+            fn next_row(&mut self) -> Result<Option<$crate::Bound<'_, $Wrap>>, $Error> {
+                Ok(($next_row)(self)?.map($Wrap::new))
+            }
+        }
+    );
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Statement {
+        values: Vec<u32>
+    }
+
+    struct Rows<'stmt> {
+        stmt: &'stmt Statement,
+        pos: usize
+    }
+
+    struct Row<'stmt> {
+        value: &'stmt u32
+    }
+
+    impl<'stmt> Rows<'stmt> {
+        fn next(&mut self) -> Result<Option<Row<'stmt>>, &'static str> {
+            match self.stmt.values.get(self.pos) {
+                Some(&0) => Err("zero"),
+                value => {
+                    self.pos += 1;
+                    Ok(value.map(|value| Row { value }))
+                }
+            }
+        }
+    }
+
+    create_gal_row_type! {
+        struct RowWrap(Row<'a>);
+
+        impl<'stmt> GeneralCursor for Rows<'stmt> {
+            type Error = &'static str;
+            next_row = Rows::next;
+            columns = |_| vec!["value"];
+        }
+    }
+
+    fn sum<C: GeneralCursor<Row=RowWrap>>(cursor: &mut C) -> Result<u32, C::Error> {
+        let mut sum = 0;
+        cursor.for_each_row(|row| sum += *RowWrap::get(&row).value)?;
+        Ok(sum)
+    }
+
+    #[test]
+    fn rows_can_be_iterated() {
+        let stmt = Statement { values: vec![1, 2, 3, 0, 4] };
+        let mut rows = Rows { stmt: &stmt, pos: 0 };
+        assert_eq!(rows.columns(), &["value"]);
+        {
+            let row = rows.next_row().unwrap().unwrap();
+            assert_eq!(*RowWrap::get(&row).value, 1);
+        }
+        assert_eq!(sum(&mut rows), Err("zero"));
+        rows.pos += 1;
+        assert_eq!(sum(&mut rows), Ok(4));
+        assert!(rows.next_row().unwrap().is_none());
+    }
+
+    #[test]
+    fn rows_can_be_iterated_fallibly() {
+        let stmt = Statement { values: vec![1, 2, 3, 0, 4] };
+        let mut rows = Rows { stmt: &stmt, pos: 0 };
+        let mut values = Vec::new();
+        let res = rows.try_for_each_row(|row| match *RowWrap::get(&row).value {
+            2 => Err("two"),
+            value => {
+                values.push(value);
+                Ok(())
+            }
+        });
+        assert_eq!(res, Err("two"));
+        assert_eq!(values, [1]);
+        assert_eq!(rows.try_for_each_row(|_| Ok(())), Err("zero"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fmt::{self, Display},
+    io
+};
+
+/// Backend agnostic classification of errors returned by connections/transactions.
+///
+/// This allows generic code (e.g. retry logic or error reporting) to handle
+/// failures without needing to know (or downcast to) the backend specific error
+/// type. Backends provide the classification by implementing [`ClassifyError`]
+/// for their error type.
+///
+/// More variants might be added in the future, so matching on it has to
+/// include a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The transaction could not be serialized wrt. concurrent transactions.
+    SerializationFailure,
+    /// The transaction was aborted due to a deadlock.
+    Deadlock,
+    /// The database (or a part of it) is locked by another connection/transaction.
+    ///
+    /// This is plain lock contention (e.g. SQLite's `SQLITE_BUSY`), no deadlock was
+    /// detected and the transaction was not found to be unserializable.
+    Busy,
+    /// The connection to the database was lost.
+    ConnectionLost,
+    /// A constraint (unique, foreign key, check, etc.) was violated.
+    ConstraintViolation,
+    /// Any other error.
+    Other
+}
+
+impl ErrorKind {
+
+    /// Returns true if retrying the failed transaction might succeed.
+    ///
+    /// This is the case for serialization failures, deadlocks and lock contention.
+    pub fn is_transient(self) -> bool {
+        matches!(self, ErrorKind::SerializationFailure | ErrorKind::Deadlock | ErrorKind::Busy)
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            ErrorKind::SerializationFailure => "serialization failure",
+            ErrorKind::Deadlock => "deadlock",
+            ErrorKind::Busy => "database busy",
+            ErrorKind::ConnectionLost => "connection lost",
+            ErrorKind::ConstraintViolation => "constraint violation",
+            ErrorKind::Other => "other error"
+        };
+        fter.write_str(msg)
+    }
+}
+
+/// Maps a backend specific error to a [`ErrorKind`].
+///
+/// Every backend integration implements this for it's error type.
+pub trait ClassifyError {
+
+    /// Returns the kind of this error.
+    fn error_kind(&self) -> ErrorKind;
+}
+
+impl ClassifyError for io::Error {
+    fn error_kind(&self) -> ErrorKind {
+        match self.kind() {
+            io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof => ErrorKind::ConnectionLost,
+            _ => ErrorKind::Other
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::io;
+    use super::*;
+
+    enum BackendError {
+        Code(u32),
+        Io(io::Error)
+    }
+
+    impl ClassifyError for BackendError {
+        fn error_kind(&self) -> ErrorKind {
+            match *self {
+                BackendError::Code(40001) => ErrorKind::SerializationFailure,
+                BackendError::Code(40002) => ErrorKind::Deadlock,
+                BackendError::Code(_) => ErrorKind::Other,
+                BackendError::Io(ref err) => err.error_kind()
+            }
+        }
+    }
+
+    fn retry<T, E: ClassifyError>(mut attempts: usize, mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        loop {
+            match op() {
+                Err(ref err) if attempts > 1 && err.error_kind().is_transient() => attempts -= 1,
+                res => return res
+            }
+        }
+    }
+
+    #[test]
+    fn generic_code_can_classify_errors() {
+        let mut calls = 0;
+        let res = retry(3, || {
+            calls += 1;
+            if calls < 3 { Err(BackendError::Code(40001)) } else { Ok(calls) }
+        });
+        assert_eq!(res.ok(), Some(3));
+
+        let res = retry(3, || -> Result<(), _> {
+            Err(BackendError::Io(io::Error::new(io::ErrorKind::BrokenPipe, "gone")))
+        });
+        assert_eq!(res.err().map(|err| err.error_kind()), Some(ErrorKind::ConnectionLost));
+    }
+}
@@ -0,0 +1,38 @@
+//! Runtime information about the optional backend integrations.
+//!
+//! Each backend integration lives in it's own sub-module of `integrations`
+//! which is only compiled if the cargo feature with the same name is enabled.
+//! Integrations do not depend on each other, so any combination of features
+//! can be enabled.
+
+/// A backend integration which can be enabled through a cargo feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backend {
+    /// The name of the backend, which is also the name of the cargo feature and module.
+    pub name: &'static str,
+    /// True if the cargo feature was enabled when compiling galemu.
+    pub active: bool
+}
+
+macro_rules! backends {
+    ($($name:tt),*) => (
+        /// All backend integrations known to this version of galemu.
+        pub const BACKENDS: &[Backend] = &[
+            $(Backend { name: $name, active: cfg!(feature = $name) }),*
+        ];
+    );
+}
+
+backends!{ "rusqlite", "postgres", "diesel" }
+
+/// Returns a iterator over the names of all active backend integrations.
+pub fn active_backends() -> impl Iterator<Item=&'static str> {
+    BACKENDS.iter()
+        .filter(|backend| backend.active)
+        .map(|backend| backend.name)
+}
+
+/// Returns true if a backend integration with the given name is active.
+pub fn is_active(name: &str) -> bool {
+    active_backends().any(|active| active == name)
+}
@@ -0,0 +1,194 @@
+/// Creates a wrapper type for boxed closures capturing a lifetime.
+///
+/// A `Box<dyn FnMut(&'a mut Context) + 'a>` has the same problem as any other
+/// type with a lifetime: it can't be a associated type. This macro defines a type
+/// alias for the boxed closure type (adding the `Box` and `+ 'a`) and wraps it using
+/// [`create_gal_wrapper_type`](crate::create_gal_wrapper_type). In addition to the
+/// functions generated by it the wrapper can be called directly through the `Bound`:
+///
+/// - `call(&Bound<'a, Wrapper>, ..)` for `Fn` closures
+/// - `call_mut(&mut Bound<'a, Wrapper>, ..)` for `FnMut` closures
+/// - `call_once(Bound<'a, Wrapper>, ..)` for `FnOnce` closures
+///
+/// Auto traits like `Send` can be placed before the closure trait (e.g. `dyn Send +
+/// FnMut(..)`), closures with up to 8 arguments are supported. Note that using the
+/// lifetime in the arguments (e.g. `&'a mut Context`) ties them to the lifetime of
+/// the `Bound`, normally the arguments should use elided lifetimes (which makes the
+/// closure accept any lifetime).
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, BoundExt};
+///
+/// struct Context { clicks: u32 }
+///
+/// trait EventSource {
+///     type Handler: for<'a> BoundExt<'a>;
+///
+///     fn emit(&mut self, context: &mut Context, handler: &mut Bound<'_, Self::Handler>);
+/// }
+///
+/// create_gal_fn_wrapper! {
+///     struct HandlerWrap(pub type Handler<'a> = dyn FnMut(&mut Context) -> u32);
+/// }
+///
+/// struct Clicks;
+///
+/// impl EventSource for Clicks {
+///     type Handler = HandlerWrap;
+///
+///     fn emit(&mut self, context: &mut Context, handler: &mut Bound<'_, HandlerWrap>) {
+///         context.clicks += 1;
+///         HandlerWrap::call_mut(handler, context);
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut log = Vec::new();
+/// {
+///     let mut handler = HandlerWrap::new(Box::new(|context: &mut Context| {
+///         log.push(context.clicks);
+///         context.clicks
+///     }));
+///     let mut context = Context { clicks: 0 };
+///     Clicks.emit(&mut context, &mut handler);
+///     Clicks.emit(&mut context, &mut handler);
+///     assert_eq!(HandlerWrap::call_mut(&mut handler, &mut context), 2);
+/// }
+/// assert_eq!(log, &[1, 2, 2]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! create_gal_fn_wrapper {
+    (@args $names:tt [ $($done:tt)* ] [] $($def:tt)*) => (
+        $crate::create_gal_fn_wrapper!{ @call $($def)* [ $($done)* ] }
+    );
+
+    (@args [ $name:ident $($names:ident)* ] [ $($done:tt)* ] [ $arg:ty $(, $args:ty)* ] $($def:tt)*) => (
+        $crate::create_gal_fn_wrapper!{ @args [ $($names)* ] [ $($done)* [ $name $arg ] ] [ $($args),* ] $($def)* }
+    );
+
+    (@args [] $done:tt $args:tt $($_ignored:tt)*) => (
+        compile_error!("closures with more than 8 arguments are not supported");
+    );
+
+    // the closure trait is the last of the traits
+    (@call [ $_auto:ident $($traits:ident)+ ] $($def:tt)*) => (
+        $crate::create_gal_fn_wrapper!{ @call [ $($traits)+ ] $($def)* }
+    );
+
+    (@call [ Fn ] [ $v:vis ] $Type:ident $lt:tt [ $ret:ty ] [ $( [ $name:ident $arg:ty ] )* ]) => (
+        impl $Type {
+            /// Calls the wrapped closure.
+            #[allow(unused)]
+            $v fn call<$lt>(me: &$crate::Bound<$lt, Self> $(, $name: $arg)*) -> $ret {
+                (Self::get(me))($($name),*)
+            }
+        }
+    );
+
+    (@call [ FnMut ] [ $v:vis ] $Type:ident $lt:tt [ $ret:ty ] [ $( [ $name:ident $arg:ty ] )* ]) => (
+        impl $Type {
+            /// Calls the wrapped closure.
+            #[allow(unused)]
+            $v fn call_mut<$lt>(me: &mut $crate::Bound<$lt, Self> $(, $name: $arg)*) -> $ret {
+                (Self::get_mut(me))($($name),*)
+            }
+        }
+    );
+
+    (@call [ FnOnce ] [ $v:vis ] $Type:ident $lt:tt [ $ret:ty ] [ $( [ $name:ident $arg:ty ] )* ]) => (
+        impl $Type {
+            /// Calls the wrapped closure, consuming it.
+            #[allow(unused)]
+            $v fn call_once<$lt>(me: $crate::Bound<$lt, Self> $(, $name: $arg)*) -> $ret {
+                (Self::into_inner(me))($($name),*)
+            }
+        }
+    );
+
+    (@call [ $Fn:ident ] $($_ignored:tt)*) => (
+        compile_error!(concat!(
+            "expected `Fn`, `FnMut` or `FnOnce`, found `", stringify!($Fn), "`"
+        ));
+    );
+
+    (
+        $(#[$attr:meta])* $v:vis struct $Type:ident(
+            $(#[$alias_attr:meta])* $alias_v:vis type $Alias:ident<$lt:tt> =
+                dyn $trait:ident $(+ $traits:ident)* ($($arg:ty),* $(,)*) -> $ret:ty
+        );
+    ) => (
+        $(#[$alias_attr])*
+        $alias_v type $Alias<$lt> = ::std::boxed::Box<dyn $trait $(+ $traits)* ($($arg),*) -> $ret + $lt>;
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Alias<$lt>); }
+
+        $crate::create_gal_fn_wrapper!{
+            @args [ arg0 arg1 arg2 arg3 arg4 arg5 arg6 arg7 ] [] [ $($arg),* ]
+                [ $trait $($traits)* ] [ $v ] $Type $lt [ $ret ]
+        }
+    );
+
+    (
+        $(#[$attr:meta])* $v:vis struct $Type:ident(
+            $(#[$alias_attr:meta])* $alias_v:vis type $Alias:ident<$lt:tt> =
+                dyn $trait:ident $(+ $traits:ident)* ($($arg:ty),* $(,)*)
+        );
+    ) => (
+        $crate::create_gal_fn_wrapper!{
+            $(#[$attr])* $v struct $Type(
+                $(#[$alias_attr])* $alias_v type $Alias<$lt> = dyn $trait $(+ $traits)* ($($arg),*) -> ()
+            );
+        }
+    );
+}
+
+
+#[cfg(test)]
+mod test {
+    use Bound;
+
+    struct Context {
+        calls: usize
+    }
+
+    create_gal_fn_wrapper! {
+        struct ObserverWrap(type Observer<'a> = dyn Fn(&Context, usize) -> usize);
+    }
+
+    create_gal_fn_wrapper! {
+        struct CounterWrap(type Counter<'a> = dyn Send + FnMut(&mut Context));
+    }
+
+    create_gal_fn_wrapper! {
+        struct FinishWrap(type Finish<'a> = dyn FnOnce() -> &'a str);
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn wrapped_closures_can_be_called() {
+        let offset = 10;
+        let observer = ObserverWrap::new(Box::new(|context: &Context, times| context.calls * times + offset));
+
+        let mut counted = 0;
+        {
+            let mut counter = CounterWrap::new(Box::new(|context: &mut Context| {
+                context.calls += 1;
+                counted += 1;
+            }));
+            assert_send(&counter);
+
+            let mut context = Context { calls: 0 };
+            CounterWrap::call_mut(&mut counter, &mut context);
+            CounterWrap::call_mut(&mut counter, &mut context);
+            assert_eq!(ObserverWrap::call(&observer, &context, 3), 16);
+        }
+        assert_eq!(counted, 2);
+
+        let name = "done".to_owned();
+        let finish: Bound<FinishWrap> = FinishWrap::new(Box::new(|| &name[..]));
+        assert_eq!(FinishWrap::call_once(finish), "done");
+    }
+}
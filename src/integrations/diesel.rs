@@ -0,0 +1,273 @@
+//! Wrapper for `diesel` transactions (requires the `diesel` feature).
+//!
+//! Diesel only provides closure based transactions (`Connection::transaction`),
+//! which can not be returned from a method. [`TransactionWrap`] instead drives
+//! the connection's `TransactionManager` directly, so that a transaction can
+//! be returned as `Bound<'conn, TransactionWrap<C>>` for any diesel connection `C`.
+//!
+//! `TransactionWrap` implements the [`Transaction`](crate::traits::Transaction) and
+//! [`Savepoint`](crate::traits::Savepoint) traits. [`Connection`](crate::traits::Connection)
+//! can not be implemented for all diesel connections directly (as it would conflict with
+//! the implementations for other backends), instead it's implemented for the
+//! [`DieselConnection`] newtype, which derefs to the wrapped connection:
+//!
+//! ```no_run
+//! # extern crate diesel;
+//! # extern crate galemu;
+//! use diesel::{Connection, SqliteConnection};
+//! use galemu::{integrations::diesel::DieselConnection, traits};
+//!
+//! fn commit_empty<C: traits::Connection>(conn: &mut C) -> Result<(), C::Error> {
+//!     let trans = conn.begin()?;
+//!     traits::Transaction::commit(trans)
+//! }
+//!
+//! # fn main() -> diesel::QueryResult<()> {
+//! let mut conn = DieselConnection(SqliteConnection::establish("app.db").unwrap());
+//! commit_empty(&mut conn)
+//! # }
+//! ```
+//!
+//! ```no_run
+//! # extern crate diesel;
+//! # extern crate galemu;
+//! use diesel::{Connection, RunQueryDsl, SqliteConnection, sql_query};
+//! use galemu::integrations::diesel::TransactionWrap;
+//!
+//! # fn main() -> diesel::QueryResult<()> {
+//! let mut conn = SqliteConnection::establish("app.db").unwrap();
+//!
+//! let mut trans = TransactionWrap::begin(&mut conn)?;
+//! sql_query("INSERT INTO items VALUES (1)").execute(TransactionWrap::get_mut(&mut trans))?;
+//! {
+//!     let mut savepoint = TransactionWrap::savepoint(&mut trans)?;
+//!     sql_query("INSERT INTO items VALUES (2)").execute(TransactionWrap::get_mut(&mut savepoint))?;
+//!     TransactionWrap::rollback(savepoint)?;
+//! }
+//! TransactionWrap::commit(trans)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::ops::{Deref, DerefMut};
+
+use diesel::{
+    connection::{Connection, TransactionManager},
+    result::{DatabaseErrorKind, Error, QueryResult}
+};
+
+use {Bound, BoundExt, ClassifyError, ErrorKind};
+use access::{AccessMode, ReadWrite};
+use traits;
+
+/// A open transaction (or savepoint) on a diesel connection.
+///
+/// If dropped without being committed the transaction is rolled back,
+/// errors from the rollback are ignored.
+pub struct TransactionWrap<C: Connection + 'static> {
+    conn: &'static mut C
+}
+
+impl<C: Connection + 'static> TransactionWrap<C> {
+
+    /// Begins a new transaction on the connection.
+    pub fn begin(conn: &mut C) -> QueryResult<Bound<'_, Self>> {
+        <C::TransactionManager as TransactionManager<C>>::begin_transaction(conn)?;
+        let conn = unsafe_block! {
+            "same type except the lifetime, which is kept by Bound" => {
+                &mut *(conn as *mut C)
+            }
+        };
+        Ok(unsafe_block! {
+            "the reference was created with the lifetime of the returned Bound" => {
+                Bound::new(TransactionWrap { conn })
+            }
+        })
+    }
+
+    /// Creates a savepoint, i.e. a nested transaction.
+    pub fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> QueryResult<Bound<'b, Self>> {
+        Self::begin(Self::get_mut(me))
+    }
+
+    /// Returns a reference to the connection the transaction runs on.
+    pub fn get<'b>(me: &'b Bound<'_, Self>) -> &'b C {
+        me.conn
+    }
+
+    /// Returns a mutable reference to the connection, use it to run queries in the transaction.
+    pub fn get_mut<'b>(me: &'b mut Bound<'_, Self>) -> &'b mut C {
+        let wrap = unsafe_block! {
+            "the reference is only reachable with a lifetime shorter than the bound one" => {
+                me._get_mut()
+            }
+        };
+        wrap.conn
+    }
+
+    /// Commits the transaction (or releases the savepoint).
+    pub fn commit(me: Bound<'_, Self>) -> QueryResult<()> {
+        let conn = me._into_inner().conn;
+        <C::TransactionManager as TransactionManager<C>>::commit_transaction(conn)
+    }
+
+    /// Rolls back the transaction (or to the savepoint).
+    pub fn rollback(me: Bound<'_, Self>) -> QueryResult<()> {
+        let conn = me._into_inner().conn;
+        <C::TransactionManager as TransactionManager<C>>::rollback_transaction(conn)
+    }
+}
+
+impl<'a, C: Connection + 'static> BoundExt<'a> for TransactionWrap<C> {
+
+    #[allow(unsafe_code)]
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        let conn = &mut *me._get_mut().conn;
+        let _ = <C::TransactionManager as TransactionManager<C>>::rollback_transaction(conn);
+        Bound::mark_pre_dropped(me);
+    }
+}
+
+impl<C: Connection + 'static> AccessMode for TransactionWrap<C> {
+    type Access = ReadWrite;
+}
+
+impl<C: Connection + 'static> traits::Transaction for TransactionWrap<C> {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> QueryResult<()> {
+        TransactionWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> QueryResult<()> {
+        TransactionWrap::rollback(me)
+    }
+}
+
+impl<C: Connection + 'static> traits::Savepoint for TransactionWrap<C> {
+    type Savepoint = TransactionWrap<C>;
+
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> QueryResult<Bound<'b, TransactionWrap<C>>> {
+        TransactionWrap::savepoint(me)
+    }
+}
+
+/// A diesel connection implementing [`traits::Connection`].
+///
+/// Dereferences to the wrapped connection, so it can be used to run queries as usual.
+pub struct DieselConnection<C: Connection + 'static>(pub C);
+
+impl<C: Connection + 'static> Deref for DieselConnection<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.0
+    }
+}
+
+impl<C: Connection + 'static> DerefMut for DieselConnection<C> {
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.0
+    }
+}
+
+impl<C: Connection + 'static> traits::Connection for DieselConnection<C> {
+    type Error = Error;
+    type Transaction = TransactionWrap<C>;
+
+    fn begin(&mut self) -> QueryResult<Bound<'_, TransactionWrap<C>>> {
+        TransactionWrap::begin(&mut self.0)
+    }
+}
+
+impl ClassifyError for Error {
+    fn error_kind(&self) -> ErrorKind {
+        match *self {
+            Error::DatabaseError(ref kind, _) => match *kind {
+                DatabaseErrorKind::SerializationFailure => ErrorKind::SerializationFailure,
+                DatabaseErrorKind::UniqueViolation
+                | DatabaseErrorKind::ForeignKeyViolation
+                | DatabaseErrorKind::NotNullViolation
+                | DatabaseErrorKind::CheckViolation => ErrorKind::ConstraintViolation,
+                DatabaseErrorKind::ClosedConnection => ErrorKind::ConnectionLost,
+                _ => ErrorKind::Other
+            },
+            _ => ErrorKind::Other
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use diesel::{Connection, RunQueryDsl, SqliteConnection, sql_query};
+    use diesel::{sql_types::BigInt, QueryableByName};
+    use {ClassifyError, ErrorKind};
+    use super::*;
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[diesel(sql_type = BigInt)]
+        count: i64
+    }
+
+    fn connection() -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        sql_query("CREATE TABLE items (id INTEGER PRIMARY KEY)").execute(&mut conn).unwrap();
+        conn
+    }
+
+    fn insert(conn: &mut SqliteConnection, id: i32) -> QueryResult<usize> {
+        sql_query(format!("INSERT INTO items VALUES ({})", id)).execute(conn)
+    }
+
+    fn count(conn: &mut SqliteConnection) -> i64 {
+        sql_query("SELECT COUNT(*) AS count FROM items").get_result::<Count>(conn).unwrap().count
+    }
+
+    #[test]
+    fn transactions_are_rolled_back_on_drop() {
+        let mut conn = connection();
+        {
+            let mut trans = TransactionWrap::begin(&mut conn).unwrap();
+            insert(TransactionWrap::get_mut(&mut trans), 1).unwrap();
+            let err = insert(TransactionWrap::get_mut(&mut trans), 1).unwrap_err();
+            assert_eq!(err.error_kind(), ErrorKind::ConstraintViolation);
+        }
+        assert_eq!(count(&mut conn), 0);
+    }
+
+    #[test]
+    fn savepoints_can_be_rolled_back() {
+        let mut conn = connection();
+        let mut trans = TransactionWrap::begin(&mut conn).unwrap();
+        insert(TransactionWrap::get_mut(&mut trans), 1).unwrap();
+        {
+            let mut savepoint = TransactionWrap::savepoint(&mut trans).unwrap();
+            insert(TransactionWrap::get_mut(&mut savepoint), 2).unwrap();
+            TransactionWrap::rollback(savepoint).unwrap();
+        }
+        TransactionWrap::commit(trans).unwrap();
+        assert_eq!(count(&mut conn), 1);
+    }
+
+    /// Runs `func` in a transaction, which is committed if `func` succeeds.
+    fn in_transaction<C, F>(conn: &mut C, func: F) -> Result<(), C::Error>
+        where C: traits::Connection, F: FnOnce(&mut Bound<C::Transaction>) -> Result<(), C::Error>
+    {
+        let mut trans = conn.begin()?;
+        func(&mut trans)?;
+        traits::Transaction::commit(trans)
+    }
+
+    #[test]
+    fn connections_can_be_used_by_generic_code() {
+        let mut conn = DieselConnection(connection());
+        in_transaction(&mut conn, |trans| insert(TransactionWrap::get_mut(trans), 1).map(drop)).unwrap();
+        let res = in_transaction(&mut conn, |trans| {
+            insert(TransactionWrap::get_mut(trans), 2)?;
+            insert(TransactionWrap::get_mut(trans), 1).map(drop)
+        });
+        assert_eq!(res.unwrap_err().error_kind(), ErrorKind::ConstraintViolation);
+        assert_eq!(count(&mut conn), 1);
+    }
+}
@@ -0,0 +1,11 @@
+//! Optional integrations with database libraries.
+//!
+//! Each integration is only available if the cargo feature with the same name
+//! is enabled (see the [`features`](crate::features) module).
+
+#[cfg(feature = "rusqlite")]
+pub mod rusqlite;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "diesel")]
+pub mod diesel;
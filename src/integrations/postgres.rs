@@ -0,0 +1,249 @@
+//! Wrappers for `postgres` transactions (requires the `postgres` feature).
+//!
+//! ```no_run
+//! # extern crate postgres;
+//! # extern crate galemu;
+//! use postgres::{Client, NoTls};
+//! use galemu::integrations::postgres::TransactionWrap;
+//!
+//! # fn main() -> Result<(), postgres::Error> {
+//! let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+//!
+//! let mut trans = TransactionWrap::begin(&mut client)?;
+//! TransactionWrap::get_mut(&mut trans).execute("INSERT INTO items VALUES (1)", &[])?;
+//! {
+//!     let mut savepoint = TransactionWrap::savepoint(&mut trans, "before_two")?;
+//!     TransactionWrap::get_mut(&mut savepoint).execute("INSERT INTO items VALUES (2)", &[])?;
+//!     TransactionWrap::rollback(savepoint)?;
+//! }
+//! TransactionWrap::commit(trans)?;
+//! # Ok(())
+//! # }
+//! ```
+use postgres::{Client, Error, Row, ToStatement, Transaction, error::SqlState, types::ToSql};
+
+use {Bound, ClassifyError, ErrorKind, create_gal_wrapper_type};
+use access::{AccessMode, ReadOnly, ReadWrite};
+use traits;
+
+create_gal_wrapper_type! {
+    /// Wraps a `postgres::Transaction` erasing it's lifetime.
+    ///
+    /// This is used for both transactions and savepoints (which are nested
+    /// transactions in `postgres`). If dropped without being committed the
+    /// transaction is rolled back.
+    pub struct TransactionWrap(Transaction<'conn>);
+}
+
+create_gal_wrapper_type! {
+    /// Wraps a read-only `postgres::Transaction` erasing it's lifetime.
+    ///
+    /// Its access mode is [`ReadOnly`] and it only gives access to the query methods
+    /// of [`ReadTransaction`]. Additionally the transaction is started as `READ ONLY`
+    /// transaction, so writes in queries fail at runtime.
+    pub struct ReadTransactionWrap(
+        /// A read-only `postgres::Transaction`, which can only run queries.
+        pub struct ReadTransaction<'conn> {
+            trans: Transaction<'conn>
+        }
+    );
+}
+
+impl TransactionWrap {
+
+    /// Begins a new transaction on the client.
+    pub fn begin(client: &mut Client) -> Result<Bound<'_, Self>, Error> {
+        Self::try_new(client.transaction())
+    }
+
+    /// Creates a savepoint (a nested transaction) with the given name.
+    pub fn savepoint<'b>(me: &'b mut Bound<'_, Self>, name: &str) -> Result<Bound<'b, Self>, Error> {
+        Self::try_new(Self::get_mut(me).savepoint(name))
+    }
+
+    /// Commits the transaction (or releases the savepoint).
+    pub fn commit(me: Bound<'_, Self>) -> Result<(), Error> {
+        Self::into_inner(me).commit()
+    }
+
+    /// Rolls back the transaction (or to the savepoint).
+    pub fn rollback(me: Bound<'_, Self>) -> Result<(), Error> {
+        Self::into_inner(me).rollback()
+    }
+}
+
+impl ReadTransactionWrap {
+
+    /// Begins a new read-only transaction on the client.
+    pub fn begin(client: &mut Client) -> Result<Bound<'_, Self>, Error> {
+        let trans = client.build_transaction().read_only(true).start()?;
+        Ok(Self::new(ReadTransaction { trans }))
+    }
+
+    /// Ends the transaction.
+    pub fn commit(me: Bound<'_, Self>) -> Result<(), Error> {
+        Self::into_inner(me).trans.commit()
+    }
+
+    /// Ends the transaction.
+    pub fn rollback(me: Bound<'_, Self>) -> Result<(), Error> {
+        Self::into_inner(me).trans.rollback()
+    }
+}
+
+impl<'conn> ReadTransaction<'conn> {
+
+    /// Runs a query returning all rows, see `postgres::Transaction::query`.
+    pub fn query<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, Error>
+        where T: ?Sized + ToStatement
+    {
+        self.trans.query(query, params)
+    }
+
+    /// Runs a query returning exactly one row, see `postgres::Transaction::query_one`.
+    pub fn query_one<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Row, Error>
+        where T: ?Sized + ToStatement
+    {
+        self.trans.query_one(query, params)
+    }
+
+    /// Runs a query returning at most one row, see `postgres::Transaction::query_opt`.
+    pub fn query_opt<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Option<Row>, Error>
+        where T: ?Sized + ToStatement
+    {
+        self.trans.query_opt(query, params)
+    }
+}
+
+impl AccessMode for TransactionWrap {
+    type Access = ReadWrite;
+}
+
+impl AccessMode for ReadTransactionWrap {
+    type Access = ReadOnly;
+}
+
+impl traits::Connection for Client {
+    type Error = Error;
+    type Transaction = TransactionWrap;
+
+    fn begin(&mut self) -> Result<Bound<'_, TransactionWrap>, Error> {
+        TransactionWrap::begin(self)
+    }
+}
+
+impl traits::ReadConnection for Client {
+    type ReadTransaction = ReadTransactionWrap;
+
+    fn begin_read(&mut self) -> Result<Bound<'_, ReadTransactionWrap>, Error> {
+        ReadTransactionWrap::begin(self)
+    }
+}
+
+impl traits::Transaction for TransactionWrap {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<(), Error> {
+        TransactionWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<(), Error> {
+        TransactionWrap::rollback(me)
+    }
+}
+
+impl traits::Savepoint for TransactionWrap {
+    type Savepoint = TransactionWrap;
+
+    /// Creates a savepoint with a name chosen by `postgres`.
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, TransactionWrap>, Error> {
+        TransactionWrap::try_new(TransactionWrap::get_mut(me).transaction())
+    }
+}
+
+impl traits::Transaction for ReadTransactionWrap {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<(), Error> {
+        ReadTransactionWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<(), Error> {
+        ReadTransactionWrap::rollback(me)
+    }
+}
+
+impl ClassifyError for Error {
+    fn error_kind(&self) -> ErrorKind {
+        if self.is_closed() {
+            return ErrorKind::ConnectionLost;
+        }
+        match self.code() {
+            Some(code) if *code == SqlState::T_R_SERIALIZATION_FAILURE => ErrorKind::SerializationFailure,
+            Some(code) if *code == SqlState::T_R_DEADLOCK_DETECTED => ErrorKind::Deadlock,
+            Some(code) if *code == SqlState::LOCK_NOT_AVAILABLE => ErrorKind::Busy,
+            // class 23: integrity constraint violation
+            Some(code) if code.code().starts_with("23") => ErrorKind::ConstraintViolation,
+            // class 08: connection exception
+            Some(code) if code.code().starts_with("08") => ErrorKind::ConnectionLost,
+            _ => ErrorKind::Other
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use postgres::{Client, NoTls};
+    use {ClassifyError, ErrorKind};
+    use super::*;
+
+    /// Connects to the database given by `GALEMU_POSTGRES_URL`.
+    ///
+    /// The tests using it are ignored by default, run them with
+    /// `GALEMU_POSTGRES_URL=... cargo test --features postgres -- --ignored`.
+    fn connect() -> Client {
+        let url = env::var("GALEMU_POSTGRES_URL")
+            .expect("GALEMU_POSTGRES_URL has to be set to run the postgres tests");
+        let mut client = Client::connect(&url, NoTls).unwrap();
+        client.batch_execute("CREATE TEMPORARY TABLE items (id INTEGER PRIMARY KEY)").unwrap();
+        client
+    }
+
+    fn count(client: &mut Client) -> i64 {
+        client.query_one("SELECT COUNT(*) FROM items", &[]).unwrap().get(0)
+    }
+
+    fn insert(trans: &mut Bound<TransactionWrap>, id: i32) -> Result<u64, Error> {
+        TransactionWrap::get_mut(trans).execute("INSERT INTO items VALUES ($1)", &[&id])
+    }
+
+    #[test]
+    #[ignore = "requires a postgres database given by GALEMU_POSTGRES_URL"]
+    fn transactions_and_savepoints_can_be_used() {
+        let mut client = connect();
+        {
+            let mut trans = TransactionWrap::begin(&mut client).unwrap();
+            insert(&mut trans, 1).unwrap();
+            {
+                let mut savepoint = TransactionWrap::savepoint(&mut trans, "sp").unwrap();
+                insert(&mut savepoint, 2).unwrap();
+            }
+            let err = insert(&mut trans, 1).unwrap_err();
+            assert_eq!(err.error_kind(), ErrorKind::ConstraintViolation);
+        }
+        assert_eq!(count(&mut client), 0);
+
+        let mut trans = TransactionWrap::begin(&mut client).unwrap();
+        insert(&mut trans, 1).unwrap();
+        TransactionWrap::commit(trans).unwrap();
+        assert_eq!(count(&mut client), 1);
+
+        let mut trans = traits::ReadConnection::begin_read(&mut client).unwrap();
+        let read = ReadTransactionWrap::get_mut(&mut trans);
+        assert_eq!(read.query_one("SELECT COUNT(*) FROM items", &[]).unwrap().get::<_, i64>(0), 1);
+        let err = read.query("INSERT INTO items VALUES (2) RETURNING id", &[]).unwrap_err();
+        assert_eq!(err.code(), Some(&SqlState::READ_ONLY_SQL_TRANSACTION));
+    }
+}
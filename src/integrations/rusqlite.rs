@@ -0,0 +1,407 @@
+//! Wrappers for `rusqlite` transactions (requires the `rusqlite` feature).
+//!
+//! ```
+//! # extern crate rusqlite;
+//! # extern crate galemu;
+//! use rusqlite::Connection;
+//! use galemu::integrations::rusqlite::{TransactionWrap, SavepointWrap};
+//!
+//! # fn main() -> rusqlite::Result<()> {
+//! let mut conn = Connection::open_in_memory()?;
+//! conn.execute("CREATE TABLE items (id INTEGER)", [])?;
+//!
+//! let mut trans = TransactionWrap::begin(&mut conn)?;
+//! TransactionWrap::get(&trans).execute("INSERT INTO items VALUES (1)", [])?;
+//! {
+//!     let savepoint = TransactionWrap::savepoint(&mut trans)?;
+//!     SavepointWrap::get(&savepoint).execute("INSERT INTO items VALUES (2)", [])?;
+//!     SavepointWrap::rollback(savepoint)?;
+//! }
+//! TransactionWrap::commit(trans)?;
+//!
+//! let count: u32 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))?;
+//! assert_eq!(count, 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Read-only transactions only give access to a [`ReadTransaction`], which provides
+//! query methods but no way to run statements changing the database:
+//!
+//! ```compile_fail
+//! # extern crate rusqlite;
+//! # extern crate galemu;
+//! use galemu::integrations::rusqlite::ReadTransactionWrap;
+//!
+//! # fn main() {
+//! let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+//! let trans = ReadTransactionWrap::begin(&mut conn).unwrap();
+//! // error: no method named `execute` found for `ReadTransaction`
+//! ReadTransactionWrap::get(&trans).execute("INSERT INTO items VALUES (1)", []);
+//! # }
+//! ```
+use rusqlite::{Connection, Error, ErrorCode, Params, Result, Row, Savepoint, Transaction};
+
+use {Bound, ClassifyError, ErrorKind, create_gal_wrapper_type};
+use access::{AccessMode, ReadOnly, ReadWrite};
+use traits;
+
+create_gal_wrapper_type! {
+    /// Wraps a `rusqlite::Transaction` erasing it's lifetime.
+    ///
+    /// If dropped without being committed the transaction is rolled back.
+    pub struct TransactionWrap(Transaction<'conn>);
+}
+
+create_gal_wrapper_type! {
+    /// Wraps a `rusqlite::Savepoint` erasing it's lifetime.
+    ///
+    /// If dropped without being committed the savepoint is rolled back.
+    pub struct SavepointWrap(Savepoint<'trans>);
+}
+
+create_gal_wrapper_type! {
+    /// Wraps a read-only `rusqlite::Transaction` erasing it's lifetime.
+    ///
+    /// Its access mode is [`ReadOnly`] and it only gives access to the query methods
+    /// of [`ReadTransaction`]. Additionally the connection is put into `query_only`
+    /// mode for the duration of the transaction, so writes in queries fail at runtime.
+    pub struct ReadTransactionWrap(
+        /// A read-only `rusqlite::Transaction`, which can only run queries.
+        pub struct ReadTransaction<'conn> {
+            trans: Transaction<'conn>
+        }
+    );
+    on_drop = |read| { let _ = read.trans.execute_batch(QUERY_ONLY_OFF); };
+}
+
+const QUERY_ONLY_ON: &str = "PRAGMA query_only = ON";
+const QUERY_ONLY_OFF: &str = "PRAGMA query_only = OFF";
+
+impl TransactionWrap {
+
+    /// Begins a new (deferred) transaction on the connection.
+    pub fn begin(conn: &mut Connection) -> Result<Bound<'_, Self>> {
+        Self::try_new(conn.transaction())
+    }
+
+    /// Creates a savepoint in the transaction.
+    pub fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, SavepointWrap>> {
+        SavepointWrap::try_new(Self::get_mut(me).savepoint())
+    }
+
+    /// Commits the transaction.
+    pub fn commit(me: Bound<'_, Self>) -> Result<()> {
+        Self::into_inner(me).commit()
+    }
+
+    /// Rolls back the transaction.
+    pub fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        Self::into_inner(me).rollback()
+    }
+}
+
+impl SavepointWrap {
+
+    /// Creates a nested savepoint.
+    pub fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, SavepointWrap>> {
+        SavepointWrap::try_new(Self::get_mut(me).savepoint())
+    }
+
+    /// Releases the savepoint, making it's changes part of the outer transaction.
+    pub fn commit(me: Bound<'_, Self>) -> Result<()> {
+        Self::into_inner(me).commit()
+    }
+
+    /// Rolls back to the savepoint and releases it.
+    pub fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        // the default drop behavior of savepoints is to rollback
+        Self::into_inner(me).finish()
+    }
+}
+
+impl ReadTransactionWrap {
+
+    /// Begins a new read-only transaction on the connection.
+    pub fn begin(conn: &mut Connection) -> Result<Bound<'_, Self>> {
+        let read = Self::new(ReadTransaction { trans: conn.transaction()? });
+        // if this fails dropping `read` resets `query_only` and rolls back
+        Self::get(&read).trans.execute_batch(QUERY_ONLY_ON)?;
+        Ok(read)
+    }
+
+    /// Ends the transaction.
+    ///
+    /// As no changes could have been made this is the same as [`ReadTransactionWrap::rollback()`].
+    pub fn commit(me: Bound<'_, Self>) -> Result<()> {
+        let trans = Self::into_inner(me).trans;
+        let reset = trans.execute_batch(QUERY_ONLY_OFF);
+        let res = trans.commit();
+        reset.and(res)
+    }
+
+    /// Ends the transaction.
+    ///
+    /// The transaction is always ended and `query_only` is reset even if the other
+    /// step fails, if both fail the error of resetting `query_only` is returned.
+    pub fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        let trans = Self::into_inner(me).trans;
+        let reset = trans.execute_batch(QUERY_ONLY_OFF);
+        let res = trans.rollback();
+        reset.and(res)
+    }
+}
+
+impl<'conn> ReadTransaction<'conn> {
+
+    /// Runs a query and maps the first row with `f`, see `rusqlite::Connection::query_row`.
+    pub fn query_row<T, P, F>(&self, sql: &str, params: P, f: F) -> Result<T>
+        where P: Params, F: FnOnce(&Row<'_>) -> Result<T>
+    {
+        self.trans.query_row(sql, params, f)
+    }
+
+    /// Runs a query and maps all rows with `f`, see `rusqlite::Statement::query_map`.
+    pub fn query_map<T, P, F>(&self, sql: &str, params: P, f: F) -> Result<Vec<T>>
+        where P: Params, F: FnMut(&Row<'_>) -> Result<T>
+    {
+        let mut stmt = self.trans.prepare(sql)?;
+        let rows = stmt.query_map(params, f)?;
+        rows.collect()
+    }
+}
+
+impl AccessMode for TransactionWrap {
+    type Access = ReadWrite;
+}
+
+impl AccessMode for SavepointWrap {
+    type Access = ReadWrite;
+}
+
+impl AccessMode for ReadTransactionWrap {
+    type Access = ReadOnly;
+}
+
+impl traits::Connection for Connection {
+    type Error = Error;
+    type Transaction = TransactionWrap;
+
+    fn begin(&mut self) -> Result<Bound<'_, TransactionWrap>> {
+        TransactionWrap::begin(self)
+    }
+}
+
+impl traits::ReadConnection for Connection {
+    type ReadTransaction = ReadTransactionWrap;
+
+    fn begin_read(&mut self) -> Result<Bound<'_, ReadTransactionWrap>> {
+        ReadTransactionWrap::begin(self)
+    }
+}
+
+impl traits::Transaction for TransactionWrap {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<()> {
+        TransactionWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        TransactionWrap::rollback(me)
+    }
+}
+
+impl traits::Savepoint for TransactionWrap {
+    type Savepoint = SavepointWrap;
+
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, SavepointWrap>> {
+        TransactionWrap::savepoint(me)
+    }
+}
+
+impl traits::Transaction for SavepointWrap {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<()> {
+        SavepointWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        SavepointWrap::rollback(me)
+    }
+}
+
+impl traits::Savepoint for SavepointWrap {
+    type Savepoint = SavepointWrap;
+
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, SavepointWrap>> {
+        SavepointWrap::savepoint(me)
+    }
+}
+
+impl traits::Transaction for ReadTransactionWrap {
+    type Error = Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<()> {
+        ReadTransactionWrap::commit(me)
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<()> {
+        ReadTransactionWrap::rollback(me)
+    }
+}
+
+/// `SQLITE_BUSY` and `SQLITE_LOCKED` are lock contention and map to `ErrorKind::Busy`.
+impl ClassifyError for Error {
+    fn error_kind(&self) -> ErrorKind {
+        match self.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked) => ErrorKind::Busy,
+            Some(ErrorCode::ConstraintViolation) => ErrorKind::ConstraintViolation,
+            _ => ErrorKind::Other
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process, time::Duration};
+    use rusqlite::Connection;
+    use {ClassifyError, ErrorKind};
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE items (id INTEGER PRIMARY KEY)", []).unwrap();
+        conn
+    }
+
+    fn count(conn: &Connection) -> u32 {
+        conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap()
+    }
+
+    fn insert(trans: &Bound<TransactionWrap>, id: u32) -> Result<usize> {
+        TransactionWrap::get(trans).execute("INSERT INTO items VALUES (?1)", [id])
+    }
+
+    #[test]
+    fn transactions_can_be_committed() {
+        let mut conn = setup();
+        let trans = TransactionWrap::begin(&mut conn).unwrap();
+        insert(&trans, 1).unwrap();
+        TransactionWrap::commit(trans).unwrap();
+        assert_eq!(count(&conn), 1);
+    }
+
+    #[test]
+    fn transactions_are_rolled_back() {
+        let mut conn = setup();
+        {
+            let trans = TransactionWrap::begin(&mut conn).unwrap();
+            insert(&trans, 1).unwrap();
+            TransactionWrap::rollback(trans).unwrap();
+        }
+        {
+            let trans = TransactionWrap::begin(&mut conn).unwrap();
+            insert(&trans, 1).unwrap();
+        }
+        assert_eq!(count(&conn), 0);
+    }
+
+    #[test]
+    fn savepoints_can_be_used() {
+        let mut conn = setup();
+        let mut trans = TransactionWrap::begin(&mut conn).unwrap();
+        {
+            let savepoint = TransactionWrap::savepoint(&mut trans).unwrap();
+            SavepointWrap::get(&savepoint).execute("INSERT INTO items VALUES (1)", []).unwrap();
+            SavepointWrap::commit(savepoint).unwrap();
+        }
+        {
+            let savepoint = TransactionWrap::savepoint(&mut trans).unwrap();
+            SavepointWrap::get(&savepoint).execute("INSERT INTO items VALUES (2)", []).unwrap();
+        }
+        TransactionWrap::commit(trans).unwrap();
+        assert_eq!(count(&conn), 1);
+    }
+
+    #[test]
+    fn savepoints_can_be_speculative() {
+        let mut conn = setup();
+        let mut trans = TransactionWrap::begin(&mut conn).unwrap();
+        {
+            let mut savepoint = TransactionWrap::savepoint(&mut trans).unwrap();
+            Bound::speculate(&mut savepoint, |nested| {
+                SavepointWrap::get(nested).execute("INSERT INTO items VALUES (1)", [])
+            }).unwrap();
+            let res = Bound::speculate(&mut savepoint, |nested| {
+                SavepointWrap::get(nested).execute("INSERT INTO items VALUES (2)", [])?;
+                SavepointWrap::get(nested).execute("INSERT INTO items VALUES (1)", [])
+            });
+            assert_eq!(res.unwrap_err().error_kind(), ErrorKind::ConstraintViolation);
+            SavepointWrap::commit(savepoint).unwrap();
+        }
+        TransactionWrap::commit(trans).unwrap();
+        let ids: Vec<u32> = conn.prepare("SELECT id FROM items").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<Result<_>>().unwrap();
+        assert_eq!(ids, [1]);
+    }
+
+    #[test]
+    fn read_transactions_can_only_query() {
+        let mut conn = setup();
+        conn.execute_batch("INSERT INTO items VALUES (1); INSERT INTO items VALUES (2)").unwrap();
+        {
+            let trans = traits::ReadConnection::begin_read(&mut conn).unwrap();
+            let read = ReadTransactionWrap::get(&trans);
+            let ids: Vec<u32> = read.query_map("SELECT id FROM items ORDER BY id", [], |row| row.get(0)).unwrap();
+            assert_eq!(ids, [1, 2]);
+            let res = read.query_row("INSERT INTO items VALUES (3) RETURNING id", [], |row| row.get::<_, u32>(0));
+            assert_eq!(res.unwrap_err().sqlite_error_code(), Some(ErrorCode::ReadOnly));
+            traits::Transaction::commit(trans).unwrap();
+        }
+        {
+            let _trans = ReadTransactionWrap::begin(&mut conn).unwrap();
+        }
+        let trans = TransactionWrap::begin(&mut conn).unwrap();
+        insert(&trans, 3).unwrap();
+        TransactionWrap::commit(trans).unwrap();
+        assert_eq!(count(&conn), 3);
+    }
+
+    #[test]
+    fn writes_succeed_after_read_transactions() {
+        let mut conn = setup();
+        ReadTransactionWrap::commit(ReadTransactionWrap::begin(&mut conn).unwrap()).unwrap();
+        conn.execute("INSERT INTO items VALUES (1)", []).unwrap();
+        ReadTransactionWrap::rollback(ReadTransactionWrap::begin(&mut conn).unwrap()).unwrap();
+        conn.execute("INSERT INTO items VALUES (2)", []).unwrap();
+        drop(ReadTransactionWrap::begin(&mut conn).unwrap());
+        conn.execute("INSERT INTO items VALUES (3)", []).unwrap();
+        assert_eq!(count(&conn), 3);
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let mut conn = setup();
+        let trans = TransactionWrap::begin(&mut conn).unwrap();
+        insert(&trans, 1).unwrap();
+        let err = insert(&trans, 1).unwrap_err();
+        assert_eq!(err.error_kind(), ErrorKind::ConstraintViolation);
+
+        let path = env::temp_dir().join(format!("galemu-busy-{}.sqlite", process::id()));
+        let mut writer = Connection::open(&path).unwrap();
+        let other = Connection::open(&path).unwrap();
+        other.busy_timeout(Duration::from_millis(0)).unwrap();
+        let trans = TransactionWrap::begin(&mut writer).unwrap();
+        TransactionWrap::get(&trans).execute("CREATE TABLE items (id INTEGER)", []).unwrap();
+        let err = other.execute("CREATE TABLE others (id INTEGER)", []).unwrap_err();
+        assert_eq!(err.error_kind(), ErrorKind::Busy);
+        assert!(err.error_kind().is_transient());
+        drop(trans);
+        drop((writer, other));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -63,7 +63,7 @@
 //! trait GeneralTransaction: for<'a> BoundExt<'a> {
 //!     // Potential results omitted.
 //!     // Once the rust "arbitrary self types" features lands on stable this can
-//!     // be made much nicer (by using `self: Bound<Self>`).
+//!     // be made much nicer (by using `self: Bound<Self>`, see below).
 //!     fn commit<'c>(me: Bound<'c, Self>);
 //!     fn abort<'c>(me: Bound<'c, Self>);
 //! }
@@ -74,15 +74,20 @@
 //! accepts a lifetime bound Self. Also not that without the unstable "arbitrary self type"
 //! feature the methods will no longer have a self parameter so they will need to be
 //! called with `GeneralTransaction::commit(trans)` instead of `trans.commit()`.
+//! On a nightly compiler the `nightly` feature enables "arbitrary self types" for this
+//! crate and the `traits` module then provides `TransactionExt`/`SavepointExt`, which
+//! add `trans.commit()` style methods to the standard traits. Your own traits can do
+//! the same by enabling the feature and using a `self: Bound<'_, Self>` receiver,
+//! `Bound` works as receiver as it implements `Deref`.
 //!
 //! The trick is that now if you need to implement `GeneralConnection` for a
 //! with transactions of the form `Transaction<'conn>` you can approach it
 //! in following way:
 //!
-//! 1. Wrap the transaction type into one mich contains a `ManualDrop<UnsafeCell<Transaction<'static>>`.
+//! 1. Wrap the transaction type into one mich contains a `ManualDrop<Transaction<'static>>`.
 //!    We call the type `TransactionWrapper`.
 //! 2. The `create_transaction(&'s mut self)` method will now internal create a transaction with the
-//!    signature `Transaction<'s>` wrap it into a `UnsafeCell` and then transmute it to `'static` erasing
+//!    signature `Transaction<'s>` wrap it into a `ManualDrop` and then transmute it to `'static` erasing
 //!    the original lifetime (we call the wr).
 //! 3. To still keep the original lifetime `'s` a `Bound<'s, TransactionWrapper>` is returned.
 //! 4. The methods on `GeneralTransaction` accept a `Bound<'c, Self>` where, due to the constraints
@@ -103,6 +108,9 @@
 //! 2. The [`BoundExt`] trait needed to handle drop wrt. to some specialization edge cases.
 //! 3. The [`create_gal_wrapper_type_for`] which implements all unsafe code for
 //!    you.
+//! 4. The [`Owned`] type for bundling a owner with a value bound to it.
+//! 5. The [`BoundVec`] type for storing multiple values bound to the same lifetime.
+//! 6. The [`traits`] module with standard `Connection`/`Transaction`/`Savepoint` traits.
 //!
 //! # Example
 //!
@@ -169,28 +177,163 @@
 //!     GTran::abort(trans)
 //! }
 //!
-//! #[test]
-//! fn it_can_be_used() {
-//!     let mut conn = Connection { count: 0 };
-//!     {
-//!         create_commit_generic(&mut conn);
-//!     }
-//!     {
-//!         create_abort_specific(&mut conn);
-//!     }
-//!     assert_eq!(conn.count, 13)
+//! let mut conn = Connection { count: 0 };
+//! {
+//!     create_commit_generic(&mut conn);
 //! }
+//! {
+//!     create_abort_specific(&mut conn);
+//! }
+//! assert_eq!(conn.count, 13)
 //! ```
 #![deny(unsafe_code)]
+#![cfg_attr(feature = "nightly", feature(arbitrary_self_types))]
+
+#[cfg(feature = "rusqlite")]
+extern crate rusqlite;
+#[cfg(feature = "postgres")]
+extern crate postgres;
+#[cfg(feature = "diesel")]
+extern crate diesel;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub extern crate serde as __serde;
+#[cfg(feature = "async_trait")]
+extern crate galemu_macros;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
+#[cfg(feature = "tokio_util")]
+extern crate tokio_util;
 
 use std::{
     marker::PhantomData,
     ops::Deref,
-    mem, ptr
+    mem::{self, ManuallyDrop},
+    ptr
 };
 
 #[macro_use]
 mod macros;
+mod policy;
+mod owned;
+mod error;
+mod bound_vec;
+mod reentrancy;
+mod static_wrap;
+mod proof;
+mod view;
+mod take;
+mod fn_wrapper;
+mod validity;
+mod sealed;
+#[cfg(feature = "const_fn")]
+#[clippy::msrv = "1.71"]
+mod const_fn;
+pub mod access;
+#[cfg(feature = "async_traits")]
+pub mod async_traits;
+#[cfg(feature = "audit_unsafe")]
+#[clippy::msrv = "1.63"]
+pub mod audit;
+#[cfg(feature = "tokio_util")]
+pub mod cancel;
+pub mod cursor;
+pub mod features;
+pub mod integrations;
+pub mod migrate;
+pub mod page;
+pub mod replay;
+#[cfg(feature = "scoped")]
+#[clippy::msrv = "1.63"]
+pub mod scoped;
+pub mod testing;
+#[cfg(feature = "tracing")]
+#[clippy::msrv = "1.63"]
+#[doc(hidden)]
+pub mod trace;
+pub mod traits;
+#[cfg(feature = "std_wrappers")]
+pub mod std_wrappers;
+
+pub use policy::PanicPolicy;
+pub use owned::Owned;
+pub use error::{ErrorKind, ClassifyError};
+pub use bound_vec::BoundVec;
+pub use reentrancy::{TokenSlot, Token, TokenMut, AlreadyInUse};
+pub use static_wrap::Static;
+pub use proof::{Outlives, LtEq};
+pub use view::{BoundRef, BoundMut};
+pub use take::Takeable;
+
+/// Rewrites the `async fn`s of a trait (or trait impl) into functions returning boxed futures.
+///
+/// Traits with `async fn`s returning `Bound` values can't be used as trait objects and
+/// writing the boxed future form by hand requires naming every elided lifetime and
+/// bounding them by the future's lifetime. This attribute does it for you, e.g.
+/// `async fn begin(&mut self) -> Result<Bound<'_, Self::Transaction>, E>` becomes:
+///
+/// ```ignore
+/// fn begin<'life0, 'galemu_async>(&'life0 mut self)
+///     -> Pin<Box<dyn Future<Output = Result<Bound<'life0, Self::Transaction>, E>> + Send + 'galemu_async>>
+///     where 'life0: 'galemu_async, Self: 'galemu_async;
+/// ```
+///
+/// Elided lifetimes in the return type refer to the lifetime of `self` (or of the only
+/// input lifetime) as they do for normal functions. The bodies of the methods are
+/// wrapped into `Box::pin(async move { .. })` moving all arguments into the future.
+///
+/// The trait and all its impls have to use the attribute. The futures are `Send`
+/// unless `#[galemu::async_trait(?Send)]` is used, in which case the trait and it's
+/// impls both have to use `?Send`.
+///
+/// This requires the `async_trait` feature and the 2018 edition or newer.
+///
+/// # Example
+///
+/// ```edition2021
+/// use galemu::{Bound, BoundExt, create_gal_wrapper_type};
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     use std::{sync::Arc, task::{Context, Poll, Wake}};
+/// #     struct Noop;
+/// #     impl Wake for Noop { fn wake(self: Arc<Self>) {} }
+/// #     let waker = Arc::new(Noop).into();
+/// #     match Box::pin(future).as_mut().poll(&mut Context::from_waker(&waker)) {
+/// #         Poll::Ready(out) => out,
+/// #         Poll::Pending => unreachable!()
+/// #     }
+/// # }
+///
+/// struct Connection { begun: usize }
+/// struct Transaction<'conn> { conn: &'conn mut Connection }
+///
+/// create_gal_wrapper_type! { struct TransactionWrap(Transaction<'a>); }
+///
+/// #[galemu::async_trait]
+/// trait AsyncConnection {
+///     type Transaction: for<'a> BoundExt<'a>;
+///
+///     async fn begin(&mut self) -> Result<Bound<'_, Self::Transaction>, String>;
+/// }
+///
+/// #[galemu::async_trait]
+/// impl AsyncConnection for Connection {
+///     type Transaction = TransactionWrap;
+///
+///     async fn begin(&mut self) -> Result<Bound<'_, TransactionWrap>, String> {
+///         self.begun += 1;
+///         Ok(TransactionWrap::new(Transaction { conn: self }))
+///     }
+/// }
+///
+/// // works with trait objects, too
+/// let conn: &mut dyn AsyncConnection<Transaction = TransactionWrap> =
+///     &mut Connection { begun: 0 };
+/// let trans = block_on(conn.begin()).unwrap();
+/// assert_eq!(TransactionWrap::get(&trans).conn.begun, 1);
+/// ```
+#[cfg(feature = "async_trait")]
+pub use galemu_macros::async_trait;
 
 /// Workaround for rust not having generic associated lifetimes (GAT/GAL).
 ///
@@ -224,9 +367,27 @@ mod macros;
 /// been static and as such might need to run the specialized code). So we have to drop
 /// it while we still have access to the original
 ///
+/// # Tracing
+///
+/// With the `tracing` feature `TRACE` level events are emitted when a bound value
+/// is created (`new`), turned into it's inner value (`into_inner`) or dropped (`drop`),
+/// the kind of event is in the `event` field. Events of `Bound` itself have the
+/// target `galemu::bound` and the type name of `T` in the `ty` field. Wrappers created
+/// with [`create_gal_wrapper_type`] additionally emit events with the target
+/// `galemu::wrapper` and the name of the wrapper in the `wrapper` field (with
+/// `pre_drop` instead of `drop`), e.g. to track down leaked transactions.
+///
+/// # Validity Checks
+///
+/// With the `validity_checks` feature `Bound` additionally tracks (in debug builds)
+/// if the inner value was already pre-dropped or moved out and panics if it's used
+/// afterwards, see [`Bound::assert_valid()`]. As this adds a field to `Bound` it
+/// no longer has the same size as `T` in this case.
 pub struct Bound<'a, T: BoundExt<'a>> {
     // bind `'a` in a invariant way
     limiter: PhantomData<&'a mut &'a u8>,
+    #[cfg(all(feature = "validity_checks", debug_assertions))]
+    validity: validity::Validity,
     inner: T
 }
 
@@ -248,8 +409,20 @@ impl<'a, T> Bound<'a, T>
     /// used by `T` to create a `Bound` wrapper of itself.
     #[allow(unsafe_code)]
     pub unsafe fn new(inner: T) -> Self {
+        #[cfg(feature = "tracing")]
+        trace::bound_event::<T>("new");
+        Bound::new_untraced(inner)
+    }
+
+    /// Like [`Bound::new()`] but without emitting a trace event.
+    ///
+    /// For temporary `Bound`s used internally, which are not visible to the user.
+    #[allow(unsafe_code)]
+    unsafe fn new_untraced(inner: T) -> Self {
         Bound {
             limiter: PhantomData,
+            #[cfg(all(feature = "validity_checks", debug_assertions))]
+            validity: validity::Validity::Valid,
             inner
         }
     }
@@ -265,6 +438,7 @@ impl<'a, T> Bound<'a, T>
     /// might brake safety constraints.
     #[allow(unsafe_code)]
     pub unsafe fn _get_mut(&mut self) -> &mut T {
+        Bound::assert_valid(self);
         &mut self.inner
     }
 
@@ -276,18 +450,258 @@ impl<'a, T> Bound<'a, T>
     /// [`BoundExt::pre_drop()`] turning this instance into `T`
     /// might cause the leakage of some resources and should
     /// only be done by methods which are aware of this problems.
-    pub fn _into_inner(mut self) -> T {
-        // workaround for having no "no-drop" destruction
-        let inner = {
-            let &mut Bound { limiter:_, ref mut inner } = &mut self;
-            unsafe_block! {
-                "self is forgotten after inner was moved out" => {
-                    ptr::read(inner as *mut T)
-                }
+    ///
+    /// With validity checks this panics if the inner value was already pre-dropped.
+    pub fn _into_inner(self) -> T {
+        #[cfg(feature = "tracing")]
+        trace::bound_event::<T>("into_inner");
+        Bound::assert_valid(&self);
+        self.into_inner_untraced()
+    }
+
+    /// Like [`Bound::_into_inner()`] but without emitting a trace event or checking validity.
+    fn into_inner_untraced(self) -> T {
+        // workaround for having no "no-drop" destruction, self is not
+        // moved (e.g. into `mem::forget`) after reading inner as this
+        // would invalidate pointers held by it (e.g. a `Box`)
+        let mut me = ManuallyDrop::new(self);
+        Bound::mark_consumed(&mut me);
+        unsafe_block! {
+            "self is never dropped so inner is only moved out once" => {
+                ptr::read(&me.inner)
             }
-        };
-        mem::forget(self);
-        inner
+        }
+    }
+
+    /// Projects a borrow of `me` to a part of it, keeping the part bound.
+    ///
+    /// `f` is called with `me` and e.g. returns a field of the inner value (using
+    /// the wrapper types `get` function). The returned reference is bound to the
+    /// borrow of `me`. See [`bound_project`] for a shorthand. For a consuming
+    /// projection (returning e.g. a `Bound<'a, FieldWrap>`) use [`Bound::erase()`].
+    pub fn project<'b, R, F>(me: &'b Self, f: F) -> Bound<'b, &'b R>
+        where R: ?Sized, F: FnOnce(&'b Self) -> &'b R
+    {
+        Bound::from(f(me))
+    }
+
+    /// Projects a mutable borrow of `me` to a part of it, keeping the part bound.
+    ///
+    /// Like [`Bound::project()`] but for mutable access.
+    pub fn project_mut<'b, R, F>(me: &'b mut Self, f: F) -> Bound<'b, &'b mut R>
+        where R: ?Sized, F: FnOnce(&'b mut Self) -> &'b mut R
+    {
+        Bound::from(f(me))
+    }
+
+    /// Returns a proof that the bound lifetime `'a` outlives the borrow of `me`.
+    ///
+    /// This is e.g. useful for unsafe code in wrapper types, which turns a
+    /// reference to the erased inner value into one with the correct lifetime.
+    pub fn outlives<'b>(_me: &'b Self) -> Outlives<'a, 'b> {
+        Outlives::new()
+    }
+
+    /// Leaks the bound value without running [`BoundExt::pre_drop()`] or any destructor.
+    ///
+    /// This is meant for cases where the inner resource was handed off to foreign
+    /// code which now is responsible for it. Like [`mem::forget`] this is safe, as
+    /// leaking is safe, but any resources owned by the inner value are leaked.
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::forget(bound)`.
+    pub fn forget(mut me: Self) {
+        Bound::mark_consumed(&mut me);
+        mem::forget(me)
+    }
+
+    /// Swaps the values of two bound slots.
+    ///
+    /// As `Bound` is invariant over `'a` both values are bound to exactly the same
+    /// lifetime, i.e. each value is valid for as long as the other slot can be
+    /// used. Values bound to different owners can be swapped, too, but then the
+    /// compiler extends both borrows to the same lifetime:
+    ///
+    /// ```compile_fail
+    /// # use galemu::{Bound, create_gal_wrapper_type};
+    /// struct Transaction<'conn> { conn: &'conn mut u32 }
+    ///
+    /// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+    ///
+    /// let mut conn1 = 0;
+    /// let mut trans1 = TransWrap::new(Transaction { conn: &mut conn1 });
+    /// {
+    ///     let mut conn2 = 0;
+    ///     let mut trans2 = TransWrap::new(Transaction { conn: &mut conn2 });
+    ///     Bound::swap(&mut trans1, &mut trans2);
+    /// }
+    /// // trans1 now borrows conn2
+    /// *TransWrap::get_mut(&mut trans1).conn += 1;
+    /// ```
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::swap(a, b)`.
+    pub fn swap(me: &mut Self, other: &mut Self) {
+        mem::swap(me, other)
+    }
+
+    /// Replaces the value in the bound slot `me` with `value` returning the old one.
+    ///
+    /// See [`Bound::swap()`] for why this is safe. The returned value is still bound
+    /// and will run `pre_drop` when dropped.
+    pub fn replace(me: &mut Self, value: Self) -> Self {
+        mem::replace(me, value)
+    }
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: Rebind<'a>
+{
+    /// Converts `me` into a value of a different wrapper type bound to the same lifetime.
+    ///
+    /// `f` is called with the inner value of `me` and has to return the inner
+    /// value of the new wrapper type. This is mainly meant to erase the concrete
+    /// type by boxing the inner value as trait object, so that values of different
+    /// types can be stored together:
+    ///
+    /// ```
+    /// # #[macro_use] extern crate galemu;
+    /// use galemu::Bound;
+    ///
+    /// trait Named {
+    ///     fn name(&self) -> &str;
+    /// }
+    ///
+    /// struct Conn(String);
+    /// struct Transaction<'conn>(&'conn Conn);
+    /// struct Statement<'conn>(&'conn Conn);
+    ///
+    /// impl<'conn> Named for Transaction<'conn> {
+    ///     fn name(&self) -> &str { &self.0 .0 }
+    /// }
+    ///
+    /// impl<'conn> Named for Statement<'conn> {
+    ///     fn name(&self) -> &str { "statement" }
+    /// }
+    ///
+    /// type DynNamed<'a> = Box<dyn Named + 'a>;
+    ///
+    /// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+    /// create_gal_wrapper_type!{ struct StmtWrap(Statement<'a>); }
+    /// create_gal_wrapper_type!{ struct DynNamedWrap(DynNamed<'a>); }
+    ///
+    /// # fn main() {
+    /// let conn = Conn("conn".to_owned());
+    /// let all: Vec<Bound<DynNamedWrap>> = vec![
+    ///     Bound::erase(TransWrap::new(Transaction(&conn)), |trans| Box::new(trans) as DynNamed),
+    ///     Bound::erase(StmtWrap::new(Statement(&conn)), |stmt| Box::new(stmt) as DynNamed),
+    /// ];
+    /// let names: Vec<_> = all.iter().map(|named| DynNamedWrap::get(named).name()).collect();
+    /// assert_eq!(names, ["conn", "statement"]);
+    /// # }
+    /// ```
+    ///
+    /// Like with [`Bound::rebind()`] a `on_drop` option of the old wrapper type is
+    /// _not_ run for the inner value.
+    pub fn erase<W, F>(me: Self, f: F) -> Bound<'a, W>
+        where W: Rebind<'a>, F: FnOnce(T::Inner) -> W::Inner
+    {
+        W::bind(f(T::unbind(me)))
+    }
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+    /// Moves the bound value into a `Box`.
+    ///
+    /// When dropped `pre_drop` of the boxed value is called as usual.
+    pub fn boxed(me: Self) -> Bound<'a, Box<T>> {
+        let boxed = Box::new(me._into_inner());
+        unsafe_block! {
+            "the boxed value is bound to 'a" => {
+                Bound::new(boxed)
+            }
+        }
+    }
+}
+
+impl<'a, T> Bound<'a, Box<T>>
+    where T: BoundExt<'a>
+{
+    /// Moves the bound value out of it's `Box`.
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::unbox(bound)`.
+    pub fn unbox(me: Self) -> Bound<'a, T> {
+        let inner = *me._into_inner();
+        unsafe_block! {
+            "the boxed value was bound to 'a" => {
+                Bound::new(inner)
+            }
+        }
+    }
+}
+
+impl<'a, T, U> Bound<'a, (T, U)>
+    where T: BoundExt<'a>, U: BoundExt<'a>
+{
+    /// Combines two values bound to the same lifetime into one bound value.
+    ///
+    /// When dropped `pre_drop` is called for `first` and then `second`.
+    pub fn zip(first: Bound<'a, T>, second: Bound<'a, U>) -> Self {
+        let inner = (first._into_inner(), second._into_inner());
+        unsafe_block! {
+            "both parts are bound to 'a" => {
+                Bound::new(inner)
+            }
+        }
+    }
+
+    /// Splits a value created with [`Bound::zip()`] into it's parts.
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::unzip(bound)`.
+    pub fn unzip(me: Self) -> (Bound<'a, T>, Bound<'a, U>) {
+        let (first, second) = me._into_inner();
+        unsafe_block! {
+            "both parts were bound to 'a" => {
+                (Bound::new(first), Bound::new(second))
+            }
+        }
+    }
+}
+
+impl<'o, T> Bound<'o, T>
+    where T: for<'x> Rebind<'x>
+{
+    /// Rebinds the value in `me` from the lifetime `'o` to `'a`.
+    ///
+    /// `f` is called with the old inner value (with it's correct lifetime `'o`)
+    /// and has to return a new inner value valid for the lifetime `'a`. As such
+    /// the lifetimes are checked by the compiler and it's not possible to
+    /// rebind to a lifetime the new value isn't valid for. Resources of the
+    /// old value (e.g. allocated buffers) can be moved into the new value.
+    ///
+    /// Note that `f` consumes the old inner value, so e.g. a `on_drop` option of
+    /// a wrapper type is _not_ run for it.
+    pub fn rebind<'a, F>(me: Self, f: F) -> Bound<'a, T>
+        where F: FnOnce(<T as Rebind<'o>>::Inner) -> <T as Rebind<'a>>::Inner
+    {
+        <T as Rebind<'a>>::bind(f(<T as Rebind<'o>>::unbind(me)))
+    }
+
+    /// Calls `f` with the inner value of `me` with it's correct lifetime `'o`.
+    ///
+    /// This allows using the wrapped value (e.g. `Transaction<'conn>`) directly
+    /// instead of going through the wrapper types `get`/`get_mut`/`into_inner`
+    /// functions. The inner value is consumed by `f`, so it's dropped (or
+    /// otherwise consumed) by it and, like with [`Bound::rebind()`], a `on_drop`
+    /// option of a wrapper type is _not_ run for it.
+    pub fn scope<R, F>(me: Self, f: F) -> R
+        where F: FnOnce(<T as Rebind<'o>>::Inner) -> R
+    {
+        f(<T as Rebind<'o>>::unbind(me))
     }
 }
 
@@ -297,6 +711,7 @@ impl<'a, T> Deref for Bound<'a, T>
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
+        Bound::assert_valid(self);
         &self.inner
     }
 }
@@ -306,6 +721,11 @@ impl<'a, T> Drop for Bound<'a, T>
     where T: BoundExt<'a>
 {
     fn drop(&mut self) {
+        #[cfg(feature = "tracing")]
+        trace::bound_event::<T>("drop");
+        if !self.valid_for_drop() {
+            return;
+        }
         unsafe_block! {
             "after this drop call rust will call drop on all members" => {
                 BoundExt::pre_drop(self)
@@ -329,7 +749,7 @@ pub trait BoundExt<'a>: 'a + Sized {
     /// This method allows to instead explicitly drop the possible "fake" `'static`
     /// value in `Self` earlier on drop while we still have the correct lifetime.
     ///
-    /// # Call Safety
+    /// # Safety
     ///
     /// This method is only meant to be called when dropping the `Bound` wrapper,
     /// i.e. immediately before calling `Self::drop`. Calling anything expect
@@ -337,173 +757,1299 @@ pub trait BoundExt<'a>: 'a + Sized {
     /// the caller has to make sure that this won't happen.
     ///
     /// Normally this should **only be called by the `Bound` `Drop` implementation**.
+    ///
+    /// The default implementation only marks `me` as pre-dropped, implementations
+    /// should do so, too (see [`Bound::mark_pre_dropped()`]).
     #[allow(unsafe_code)]
-    unsafe fn pre_drop(_me: &mut Bound<'a, Self>) {}
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        Bound::mark_pre_dropped(me)
+    }
 }
 
-/// Creates a wrapper type for a type with a single lifetime parameter lifting the lifetime to `Bound`.
-///
-/// The new type will have:
-/// - A safe `new` method accepting a instance of the wrapped type with a lifetime
-///   `'a` and returns a `Bound<'a, WrapperType>`.
-/// - Impl for `BoundExt` incl, `BoundExt::pre_drop` (the wrapper doesn't need a `Drop` impl.).
-/// - A `get` function which accept `&Bound<'a, WrapperType>` and returns a `&WrappedType<'a>`.
-/// - A `get_mut` function which accepts `&mut Bound<'a, WrapperType>` and returns a `&mut WrappedType<'a>`.
-/// - A `into_inner` function which accpets a `Bound<'a, WrapperType>` and returns a `WrappedType<'a>`.
-///
-/// Note that all the above functions are implemented on the wrapper type, i.e. you can't be
-/// generic over them (at last not without generic associated lifetimes).
-///
-///
-/// # Example
-///
-/// See module level documentation.
-#[macro_export]
-macro_rules! create_gal_wrapper_type {
-
-    ( $(#[$attr:meta])* $v:vis struct $Type:ident ($Inner:ident<$lt:tt>); ) => (
-
-        $(#[$attr])*
-        $v struct $Type {
-            static_cell: ::std::mem::ManuallyDrop<::std::cell::UnsafeCell<$Inner<'static>>>
-        }
-
-        impl $Type {
-
-            /// Create a new "bound" instance of this type.
-            ///
-            /// This will lift the lifetime from the inner type to the `Bound` wrapper,
-            /// wrapping the inner type into this type while erasing it's lifetime
-            $v fn new<$lt>(value: $Inner<$lt>) -> $crate::Bound<$lt, Self> {
-                use std::{ mem::{self, ManuallyDrop}, cell::UnsafeCell };
-
-                let cell = ManuallyDrop::new(UnsafeCell::new(value));
-                $crate::unsafe_block! {
-                    "same mem layout, the unsafe cell contains the wrong lifetime in check" => {
-                        let static_cell = mem::transmute(cell);
-                        Bound::new($Type { static_cell })
-                    }
-                }
-            }
-
-            #[allow(unused)]
-            $v fn get<'s: 'b, 'b>(me: &'b Bound<'s, Self>) -> &'b $Inner<'s> {
-                let ptr: *const $Inner<'static> = me.static_cell.get();
-                $crate::unsafe_block! {
-                    "Self was transmuted from $Inner and `'s` is valid due to Bound's guarantees" => {
-                        let as_ref: &'b $Inner<'static> = &*ptr;
-                        ::std::mem::transmute(as_ref)
-                    }
-                }
-            }
-
-            #[allow(unused)]
-            $v fn get_mut<'s: 'b, 'b>(me: &'b mut Bound<'s, Self>) -> &'b mut $Inner<'s> {
-                let ptr: *mut $Inner<'static> = me.static_cell.get();
-                $crate::unsafe_block! {
-                    "Self was transmuted from $Inner and `'s` is valid due to Bound's guarantees" => {
-                        let as_mut: &'b mut $Inner<'static> = &mut *ptr;
-                        ::std::mem::transmute(as_mut)
-                    }
-                }
-            }
-
-            #[allow(unused)]
-            $v fn into_inner<'s>(me: Bound<'s, Self>) -> $Inner<'s> {
-                use std::{ mem::{self, ManuallyDrop}, cell::UnsafeCell };
+// There is no blanket impl for all types, as it would prevent types from providing
+// their own `pre_drop` (at last without specialization). Splitting `Bound` into a core
+// type without the `BoundExt` bound and a `pre_drop` aware one doesn't help either, as
+// the `Drop` impl of the core type would need the same (i.e. no) bounds as the type and
+// so couldn't call `pre_drop`. Instead types which borrow with the bound lifetime itself
+// are supported out of the box, for types which don't borrow anything see `Static`.
+//
+// Other crates could implement `BoundExt<'a>` for `&'a TheirType` before, adding these
+// impls broke such impls (see the change log).
 
-                let $Type { static_cell } = me._into_inner();
+/// Plain shared references to `'a` can be put into `Bound<'a, _>`, e.g. using `Bound::from`.
+impl<'a, T> BoundExt<'a> for &'a T
+    where T: ?Sized + 'a
+{}
 
-                let non_static_cell = $crate::unsafe_block! {
-                    "the $Inner<'static> originally had been a $Inner<'s>" => {
-                        mem::transmute::<
-                            ManuallyDrop<UnsafeCell<$Inner<'static>>>,
-                            ManuallyDrop<UnsafeCell<$Inner<'s>>>
-                        >(static_cell)
-                    }
-                };
+/// Plain mutable references to `'a` can be put into `Bound<'a, _>`, e.g. using `Bound::from`.
+impl<'a, T> BoundExt<'a> for &'a mut T
+    where T: ?Sized + 'a
+{}
 
-                ManuallyDrop::into_inner(non_static_cell).into_inner()
+impl<'a, T> From<&'a T> for Bound<'a, &'a T>
+    where T: ?Sized + 'a
+{
+    fn from(value: &'a T) -> Self {
+        unsafe_block! {
+            "the reference is valid for 'a" => {
+                Bound::new(value)
             }
         }
+    }
+}
 
-        impl<'a> $crate::BoundExt<'a> for $Type {
-
-            #[allow(unsafe_code)]
-            unsafe fn pre_drop(me: &mut $crate::Bound<'a, Self>) {
-                use std::{mem::{self, ManuallyDrop}, cell::UnsafeCell};
-
-                // Safe due to the constraints of only calling drop after pre_drop
-                let static_as_mut: &mut ManuallyDrop<UnsafeCell<$Inner<'static>>> = &mut me._get_mut().static_cell;
-                let as_mut: &mut ManuallyDrop<UnsafeCell<$Inner<'a>>> = mem::transmute(static_as_mut);
-                ManuallyDrop::drop(as_mut)
+impl<'a, T> From<&'a mut T> for Bound<'a, &'a mut T>
+    where T: ?Sized + 'a
+{
+    fn from(value: &'a mut T) -> Self {
+        unsafe_block! {
+            "the reference is valid for 'a" => {
+                Bound::new(value)
             }
         }
+    }
+}
 
-    );
+/// Runs `pre_drop` for a part of a bound value, the part is dropped afterwards as usual.
+///
+/// # Safety
+///
+/// Same as for [`BoundExt::pre_drop()`], `part` has to be valid for `'a` and
+/// must not be used in any way except being dropped after this call.
+#[allow(unsafe_code)]
+unsafe fn pre_drop_part<'a, T>(part: &mut T)
+    where T: BoundExt<'a>
+{
+    // read/write instead of casting `&mut T` as `Bound`'s layout isn't fixed,
+    // if `pre_drop` panics the part is only dropped by the drop glue of the owner,
+    // the temporary `Bound` is an implementation detail so it's not traced
+    let mut bound = ManuallyDrop::new(Bound::new_untraced(ptr::read(part)));
+    BoundExt::pre_drop(&mut *bound);
+    ptr::write(part, ManuallyDrop::into_inner(bound).into_inner_untraced());
 }
 
+/// Pre-drops both parts in order, the parts are then dropped in the same order.
+impl<'a, T, U> BoundExt<'a> for (T, U)
+    where T: BoundExt<'a>, U: BoundExt<'a>
+{
+    #[allow(unsafe_code)]
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        {
+            let &mut (ref mut first, ref mut second) = me._get_mut();
+            pre_drop_part(first);
+            pre_drop_part(second);
+        }
+        Bound::mark_pre_dropped(me);
+    }
+}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+impl<'a, T, U> Rebind<'a> for (T, U)
+    where T: Rebind<'a>, U: Rebind<'a>
+{
+    type Inner = (T::Inner, U::Inner);
 
-    struct Connection {
-        count: usize
+    fn unbind(me: Bound<'a, Self>) -> Self::Inner {
+        let (first, second) = Bound::unzip(me);
+        (T::unbind(first), U::unbind(second))
     }
 
-    struct Transaction<'conn> {
-        conn: &'conn mut Connection
+    fn bind(inner: Self::Inner) -> Bound<'a, Self> {
+        Bound::zip(T::bind(inner.0), U::bind(inner.1))
     }
+}
 
-    impl Connection {
-        fn transaction(&mut self) -> Transaction {
-            Transaction { conn: self }
-        }
+impl<'a, T> BoundExt<'a> for Box<T>
+    where T: BoundExt<'a>
+{
+    #[allow(unsafe_code)]
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        pre_drop_part(&mut **me._get_mut());
+        Bound::mark_pre_dropped(me);
     }
+}
 
-    trait GCon {
-        type Transaction: GTran;
+impl<'a, T> Rebind<'a> for Box<T>
+    where T: Rebind<'a>
+{
+    type Inner = T::Inner;
 
-        fn create_transaction(&mut self) -> Bound<Self::Transaction>;
+    fn unbind(me: Bound<'a, Self>) -> Self::Inner {
+        T::unbind(Bound::unbox(me))
     }
 
-    trait GTran: for<'s> BoundExt<'s> {
-        fn commit<'s>(me: Bound<'s, Self>);
-        fn abort<'s>(me: Bound<'s, Self>);
+    fn bind(inner: Self::Inner) -> Bound<'a, Self> {
+        Bound::boxed(T::bind(inner))
     }
+}
 
-    create_gal_wrapper_type!{
-        /// Wraps `Transaction` erasing it's lifetime.
-        ///
-        /// This can only be used through a `Bound<'a, TransWrap>` instance,
-        /// as only then it is possible to access the wrapped type with the
-        /// correct lifetime
-        struct TransWrap(Transaction<'a>);
-    }
+/// Provides generic access to the inner value of a wrapper type.
+///
+/// This mainly exists to allow reusing a bound value for a new lifetime
+/// through [`Bound::rebind`] instead of fully reconstructing it, which can
+/// matter for hot paths where many short lived bound values are created
+/// (e.g. prepared statements rebound to a new transaction reusing their
+/// buffers).
+///
+/// It is implemented by all types created with [`create_gal_wrapper_type`],
+/// pairs of them (as created by [`Bound::zip()`]), boxes of them and [`Static`].
+pub trait Rebind<'a>: BoundExt<'a> {
 
-    impl GCon for Connection {
-        type Transaction = TransWrap;
+    /// The type wrapped by `Self` with the lifetime `'a`.
+    type Inner;
 
-        fn create_transaction(&mut self) -> Bound<Self::Transaction> {
-            let transaction = self.transaction();
-            TransWrap::new(transaction)
-        }
-    }
+    /// Turns the bound value into the inner value with the correct lifetime.
+    fn unbind(me: Bound<'a, Self>) -> Self::Inner;
 
-    impl GTran for TransWrap {
-        fn commit<'s>(me: Bound<'s, Self>) {
-            let trans = TransWrap::into_inner(me);
-            trans.conn.count += 10;
-        }
+    /// Wraps the inner value binding it's lifetime to `Bound`.
+    fn bind(inner: Self::Inner) -> Bound<'a, Self>;
+}
 
-        fn abort<'s>(me: Bound<'s, Self>) {
-            let trans = TransWrap::into_inner(me);
-            trans.conn.count += 3;
-        }
-    }
+/// Creates a value bound to `owner` using `make` and passes it to `body`.
+///
+/// As `body` has to work with any lifetime it can not leak the bound value (or
+/// anything borrowing from it), making it a closure based alternative to storing
+/// the bound value. Combined with [`Bound::scope()`] the inner value can be used
+/// directly:
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, with_bound};
+///
+/// struct Connection { committed: bool }
+/// struct Transaction<'conn> { conn: &'conn mut Connection }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// # fn main() {
+/// let mut conn = Connection { committed: false };
+/// with_bound(&mut conn, |conn| TransWrap::new(Transaction { conn }), |trans| {
+///     Bound::scope(trans, |trans| trans.conn.committed = true)
+/// });
+/// assert!(conn.committed);
+/// # }
+/// ```
+pub fn with_bound<O, T, M, F, R>(owner: &mut O, make: M, body: F) -> R
+    where T: for<'a> BoundExt<'a>,
+          M: for<'a> FnOnce(&'a mut O) -> Bound<'a, T>,
+          F: for<'a> FnOnce(Bound<'a, T>) -> R
+{
+    body(make(owner))
+}
 
-    fn create_commit_generic(x: &mut impl GCon) {
+/// Creates a wrapper type for a type with a single lifetime parameter lifting the lifetime to `Bound`.
+///
+/// The new type will have:
+/// - A safe `new` method accepting a instance of the wrapped type with a lifetime
+///   `'a` and returns a `Bound<'a, WrapperType>`.
+/// - A safe `try_new` method accepting a `Result<WrappedType<'a>, E>` and returning
+///   a `Result<Bound<'a, WrapperType>, E>`.
+/// - Impl for `BoundExt` incl, `BoundExt::pre_drop` (the wrapper doesn't need a `Drop` impl.).
+/// - A `get` function which accept `&Bound<'a, WrapperType>` and returns a `&WrappedType<'a>`.
+/// - A `get_mut` function which accepts `&mut Bound<'a, WrapperType>` and returns a `&mut WrappedType<'a>`.
+/// - A `into_inner` function which accpets a `Bound<'a, WrapperType>` and returns a `WrappedType<'a>`.
+/// - A impl of [`Rebind`] with `Inner = WrappedType<'a>` (if it has no extra fields, see below).
+///
+/// Note that all the above functions are implemented on the wrapper type, i.e. you can't be
+/// generic over them (at last not without generic associated lifetimes).
+///
+/// The wrapper is `#[repr(transparent)]` (if it has no extra fields, see below), i.e. it has
+/// the same layout as the wrapped type incl. niches, so e.g. `Option<Bound<'a, WrapperType>>`
+/// is pointer sized if the wrapped type is a pointer.
+///
+/// # Options
+///
+/// Following options can be placed after the wrapper struct definition:
+///
+/// - `on_drop = <closure>;` runs the closure with the correctly-lifetimed inner value
+///   when the `Bound` wrapper is dropped (instead of just dropping the inner value).
+///   It is not run if the inner value was retrieved through `into_inner`. This is
+///   e.g. useful to rollback transactions which where not explicitly committed.
+///   The closure must not capture anything as it's converted to a `fn` pointer.
+/// - `on_panic = <variant>;` sets the [`PanicPolicy`] variant used if dropping the
+///   inner value (or the `on_drop` closure) panics, e.g. `on_panic = Swallow;`.
+///   Defaults to `Resume`.
+/// - `rename = { <function>: <name>, .. };` renames the generated functions, e.g. to
+///   avoid collisions with functions you want to define on the wrapper type. `get`,
+///   `get_mut` and `try_new` can also be suppressed by "renaming" them to `_`.
+/// - `forward = [<trait>, ..];` implements `AsRef<Inner<'a>>`, `Borrow<Inner<'a>>`
+///   and/or `AsMut<Inner<'a>>` for `Bound<'a, WrapperType>` by forwarding to `get`
+///   respectively `get_mut`, so bound values can be passed to code generic over
+///   these traits. Nothing is forwarded by default, as e.g. `Borrow` comes with
+///   requirements wrt. `Eq`/`Hash` which might not be wanted. With the `serde`
+///   feature `Serialize` can be forwarded, too (see below).
+/// - `convert fn <method> -> <WrapperType>;` generates a function with the same name
+///   converting a `Bound<'a, ThisWrapper>` into a `Bound<'a, WrapperType>` by calling
+///   the method on the inner value (which has to return the inner type of the other
+///   wrapper). The other wrapper has to implement [`Rebind`] (i.e. has no extra fields).
+/// - `unsafe impl Send;` and/or `unsafe impl Sync;` implement the auto trait for the
+///   wrapper, for wrapped types which are thread safe but don't implement it (e.g. because
+///   they contain raw pointers). This is not needed if the wrapped type implements them,
+///   as the wrapper then implements them automatically, as does `Bound<'a, WrapperType>`
+///   (see the `scoped` module, enabled by the feature with the same name, for using bound
+///   values in scoped threads).
+/// - `owned = <OwnedType>;` declares a owned counterpart of the wrapped type and generates
+///   `to_owned(&Bound<'a, WrapperType>)`, which copies the inner value into it (usable if
+///   `OwnedType: From<&Inner<'a>>`), and `from_owned(&'a OwnedType)`, which creates a bound
+///   value borrowing from it (usable if `Inner<'a>: From<&'a OwnedType>` and the wrapper
+///   has no extra fields). This allows escaping the lifetime when needed by paying for a
+///   copy. It requires `get` to not be suppressed.
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct Transaction<'conn> { log: &'conn mut Vec<&'static str> }
+///
+/// impl<'conn> Transaction<'conn> {
+///     fn rollback(self) { self.log.push("rollback") }
+/// }
+///
+/// create_gal_wrapper_type! {
+///     struct TransWrap(Transaction<'a>);
+///     on_drop = |trans| trans.rollback();
+/// }
+///
+/// let mut log = Vec::new();
+/// {
+///     let _trans = TransWrap::new(Transaction { log: &mut log });
+/// }
+/// assert_eq!(log, &["rollback"]);
+/// ```
+///
+/// ```
+/// # use galemu::{Bound, create_gal_wrapper_type};
+/// struct Transaction<'conn> { id: &'conn u32 }
+///
+/// create_gal_wrapper_type! {
+///     struct TransWrap(Transaction<'a>);
+///     rename = { get: gal_get, get_mut: _ };
+/// }
+///
+/// impl TransWrap {
+///     fn get(me: &Bound<Self>) -> u32 {
+///         *Self::gal_get(me).id
+///     }
+/// }
+///
+/// let id = 12;
+/// assert_eq!(TransWrap::get(&TransWrap::new(Transaction { id: &id })), 12);
+/// ```
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct Transaction<'conn> { log: &'conn mut Vec<&'static str> }
+///
+/// create_gal_wrapper_type! {
+///     struct TransWrap(Transaction<'a>);
+///     forward = [AsRef, AsMut];
+/// }
+///
+/// fn log_insert<'a, T: AsMut<Transaction<'a>>>(mut trans: T) {
+///     trans.as_mut().log.push("insert");
+/// }
+///
+/// let mut log = Vec::new();
+/// {
+///     let mut trans = TransWrap::new(Transaction { log: &mut log });
+///     log_insert(&mut trans);
+///     assert_eq!(trans.as_ref().log.len(), 1);
+/// }
+/// assert_eq!(log, &["insert"]);
+/// ```
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct Transaction<'conn> { conn: &'conn mut u32 }
+/// struct Savepoint<'conn> { conn: &'conn mut u32 }
+///
+/// impl<'conn> Transaction<'conn> {
+///     fn into_savepoint(self) -> Savepoint<'conn> {
+///         Savepoint { conn: self.conn }
+///     }
+/// }
+///
+/// create_gal_wrapper_type! { struct SavepointWrap(Savepoint<'a>); }
+///
+/// create_gal_wrapper_type! {
+///     struct TransWrap(Transaction<'a>);
+///     convert fn into_savepoint -> SavepointWrap;
+/// }
+///
+/// let mut conn = 0;
+/// let trans = TransWrap::new(Transaction { conn: &mut conn });
+/// let mut savepoint = TransWrap::into_savepoint(trans);
+/// *SavepointWrap::get_mut(&mut savepoint).conn += 1;
+/// ```
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct Row<'stmt> { name: &'stmt str }
+///
+/// struct RowData { name: String }
+///
+/// impl<'a, 'stmt> From<&'a Row<'stmt>> for RowData {
+///     fn from(row: &'a Row<'stmt>) -> Self {
+///         RowData { name: row.name.to_owned() }
+///     }
+/// }
+///
+/// impl<'a> From<&'a RowData> for Row<'a> {
+///     fn from(data: &'a RowData) -> Self {
+///         Row { name: &data.name }
+///     }
+/// }
+///
+/// create_gal_wrapper_type! {
+///     struct RowWrap(Row<'a>);
+///     owned = RowData;
+/// }
+///
+/// let data = {
+///     let stmt = String::from("alice");
+///     let row = RowWrap::new(Row { name: &stmt });
+///     RowWrap::to_owned(&row)
+/// };
+/// let row = RowWrap::from_owned(&data);
+/// assert_eq!(RowWrap::get(&row).name, "alice");
+/// ```
+///
+/// With the `serde` feature `forward = [Serialize];` implements `serde::Serialize`
+/// for `Bound<'a, WrapperType>` by serializing the value returned by `get`, e.g.
+/// to write query results wrapped in `Bound` straight into a JSON response.
+/// `Deserialize` is not supported: a bound value borrows from some owner (e.g.
+/// a connection or lock) which can not be recreated from serialized data.
+/// Wrapped types which just borrow from the deserializer's input can be
+/// deserialized directly and then passed to `new`.
+///
+/// # Inline Definitions
+///
+/// Instead of the name of the wrapped type a definition of a `enum` or a struct
+/// can be given, which is then defined next to the wrapper type.
+/// This is e.g. useful for wrapping a type multiplexing over multiple backends:
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct SqliteTransaction<'conn>(&'conn mut u32);
+/// struct PgTransaction<'conn>(&'conn mut u64);
+///
+/// create_gal_wrapper_type! {
+///     pub struct AnyTransactionWrap(
+///         /// A transaction of any of the supported backends.
+///         pub enum AnyTransaction<'conn> {
+///             Sqlite(SqliteTransaction<'conn>),
+///             Pg(PgTransaction<'conn>)
+///         }
+///     );
+/// }
+///
+/// let mut count = 0;
+/// let trans = AnyTransactionWrap::new(AnyTransaction::Sqlite(SqliteTransaction(&mut count)));
+/// match AnyTransactionWrap::into_inner(trans) {
+///     AnyTransaction::Sqlite(trans) => *trans.0 += 1,
+///     AnyTransaction::Pg(trans) => *trans.0 += 1
+/// }
+/// assert_eq!(count, 1);
+/// ```
+///
+/// This can also be used to bind multiple values to the same lifetime, e.g. a
+/// transaction and a borrowed schema. `get`, `get_mut` and `into_inner` then
+/// give access to all of them with the correct lifetime and on drop they are
+/// dropped in declaration order (like any struct fields):
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct Schema { tables: Vec<&'static str> }
+/// struct Transaction<'conn> { log: &'conn mut Vec<&'static str> }
+///
+/// create_gal_wrapper_type! {
+///     struct SchemaTransWrap(struct SchemaTrans<'a>(Transaction<'a>, &'a Schema));
+/// }
+///
+/// let schema = Schema { tables: vec!["users"] };
+/// let mut log = Vec::new();
+/// {
+///     let mut both = SchemaTransWrap::new(SchemaTrans(Transaction { log: &mut log }, &schema));
+///     let SchemaTrans(trans, schema) = SchemaTransWrap::get_mut(&mut both);
+///     trans.log.extend(schema.tables.iter());
+/// }
+/// assert_eq!(log, &["users"]);
+/// ```
+///
+/// # Type Aliases and Trait Objects
+///
+/// A type alias definition can be given, too. This allows wrapping types which don't
+/// have a single lifetime parameter, e.g. `Box<dyn Query + 'a>` if the concrete type
+/// differs per backend and can't be named. As trait objects are unsized `dyn Trait + 'a`
+/// is a shorthand for the boxed form. The wrapper then works the same as for any other
+/// type, e.g. `on_drop` gets the boxed trait object with the correct lifetime and the
+/// trait object's `Drop` impl runs as part of `pre_drop`:
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// trait Query {
+///     fn fetch(&mut self) -> Option<u32>;
+///     fn close(self: Box<Self>);
+/// }
+///
+/// struct SqliteQuery<'conn> { rows: &'conn mut Vec<u32> }
+///
+/// impl<'conn> Query for SqliteQuery<'conn> {
+///     fn fetch(&mut self) -> Option<u32> { self.rows.pop() }
+///     fn close(self: Box<Self>) { self.rows.clear() }
+/// }
+///
+/// create_gal_wrapper_type! {
+///     struct QueryWrap(pub type AnyQuery<'a> = dyn Query + Send + 'a);
+///     on_drop = |query| query.close();
+/// }
+///
+/// let mut rows = vec![1, 2, 3];
+/// {
+///     let mut query = QueryWrap::new(Box::new(SqliteQuery { rows: &mut rows }));
+///     assert_eq!(QueryWrap::get_mut(&mut query).fetch(), Some(3));
+/// }
+/// assert!(rows.is_empty());
+/// ```
+///
+/// # Extra Fields
+///
+/// The wrapper can also be defined as struct with named fields, in which case the
+/// first field has to be marked with `#[bound]` and contains the wrapped type. All
+/// other fields are normal fields, which can be accessed through `&Bound` as it
+/// implements `Deref`. The values of the other fields are passed to `new`/`try_new`
+/// after the wrapped value. As `Rebind::bind` couldn't provide them, `Rebind` is not
+/// implemented for wrappers with extra fields and `into_inner` drops them.
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// use std::time::Instant;
+///
+/// struct Statement<'conn> { sql: &'conn str }
+///
+/// create_gal_wrapper_type! {
+///     struct StatementWrap {
+///         #[bound] stmt: Statement<'a>,
+///         pub prepared_at: Instant,
+///         pub executions: usize,
+///     }
+/// }
+///
+/// let sql = "SELECT 1".to_owned();
+/// let stmt = StatementWrap::new(Statement { sql: &sql }, Instant::now(), 0);
+/// assert_eq!(stmt.executions, 0);
+/// assert_eq!(StatementWrap::get(&stmt).sql, "SELECT 1");
+/// ```
+///
+/// # Generic Parameters
+///
+/// The wrapped type can have const and type parameters after it's lifetime, in which case
+/// the wrapper needs them as generic parameters, too. The arguments of the wrapped type can
+/// be any types or names of const parameters of the wrapper. Type parameters can have
+/// defaults and trait bounds (paths without generic arguments), const parameters can have
+/// defaults, too. Type parameters are always bound by `'static`, as the wrapper has to be
+/// `'static` (the lifetime is erased after all). Generic parameters are not supported
+/// together with inline definitions.
+///
+/// ```
+/// # use galemu::create_gal_wrapper_type;
+/// struct ChunkReader<'a, T, const N: usize> { source: &'a [T], chunk: [T; N] }
+///
+/// create_gal_wrapper_type! {
+///     struct ChunkReaderWrap<const N: usize, T: Copy = u8>(ChunkReader<'a, T, N>);
+/// }
+///
+/// let data = vec![1, 2, 3];
+/// let reader = ChunkReaderWrap::<4>::new(ChunkReader { source: &data, chunk: [0; 4] });
+/// assert_eq!(ChunkReaderWrap::get(&reader).chunk.len(), 4);
+/// ```
+///
+/// # Example
+///
+/// See module level documentation.
+#[macro_export]
+macro_rules! create_gal_wrapper_type {
+
+    (@options $head:tt { on_drop: $_old:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        on_drop = $on_drop:expr; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: { $on_drop }, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $_old:tt, names: $names:tt,
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        on_panic = $on_panic:ident; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: [ $($old:ident)* ], convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        forward = [ $($trait:ident),* $(,)* ]; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head {
+                on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: [ $($old)* $($trait)* ], convert: $convert, impls: $impls, owned: $owned
+            } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: $forward:tt, convert: [ $($old:tt)* ], impls: $impls:tt, owned: $owned:tt }
+        convert fn $name:ident -> $Target:ty; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head {
+                on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: [ $($old)* [ $name $Target ] ], impls: $impls, owned: $owned
+            } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: $forward:tt, convert: $convert:tt, impls: [ $($old:ident)* ], owned: $owned:tt }
+        unsafe impl $auto:ident; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head {
+                on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: $convert, impls: [ $($old)* $auto ], owned: $owned
+            } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $_old:tt }
+        owned = $Owned:ty; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head {
+                on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: $convert, impls: $impls, owned: [ $Owned ]
+            } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt, names: $names:tt,
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = {}; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic, names: $names,
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned } $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt,
+            names: [ $_old:tt $try_new:tt $get:tt $get_mut:tt $into_inner:tt ],
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = { new: $new:ident $(, $key:ident: $name:tt)* $(,)* }; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic,
+                names: [ $new $try_new $get $get_mut $into_inner ],
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned }
+            rename = { $($key: $name),* }; $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt,
+            names: [ $new:tt $_old:tt $get:tt $get_mut:tt $into_inner:tt ],
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = { try_new: $try_new:tt $(, $key:ident: $name:tt)* $(,)* }; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic,
+                names: [ $new $try_new $get $get_mut $into_inner ],
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned }
+            rename = { $($key: $name),* }; $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt,
+            names: [ $new:tt $try_new:tt $_old:tt $get_mut:tt $into_inner:tt ],
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = { get: $get:tt $(, $key:ident: $name:tt)* $(,)* }; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic,
+                names: [ $new $try_new $get $get_mut $into_inner ],
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned }
+            rename = { $($key: $name),* }; $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt,
+            names: [ $new:tt $try_new:tt $get:tt $_old:tt $into_inner:tt ],
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = { get_mut: $get_mut:tt $(, $key:ident: $name:tt)* $(,)* }; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic,
+                names: [ $new $try_new $get $get_mut $into_inner ],
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned }
+            rename = { $($key: $name),* }; $($rest)*
+        }
+    );
+
+    (@options $head:tt { on_drop: $on_drop:tt, on_panic: $on_panic:tt,
+            names: [ $new:tt $try_new:tt $get:tt $get_mut:tt $_old:tt ],
+            forward: $forward:tt, convert: $convert:tt, impls: $impls:tt, owned: $owned:tt }
+        rename = { into_inner: $into_inner:ident $(, $key:ident: $name:tt)* $(,)* }; $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options $head { on_drop: $on_drop, on_panic: $on_panic,
+                names: [ $new $try_new $get $get_mut $into_inner ],
+                forward: $forward, convert: $convert, impls: $impls, owned: $owned }
+            rename = { $($key: $name),* }; $($rest)*
+        }
+    );
+
+    (@options [ [ $(#[$attr:meta])* ] [ $v:vis ] [ $Type:ident ] [ $Inner:ident ] [ $lt:tt ]
+        [ $cell:ident ] [ $( [ [ $(#[$field_attr:meta])* ] [ $field_v:vis ] [ $field:ident ] [ $field_ty:ty ] ] )* ]
+        [ $($iarg:ty),* ] [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ] ]
+        { on_drop: $on_drop:tt, on_panic: $on_panic:ident,
+            names: [ $new:ident $try_new:tt $get:tt $get_mut:tt $into_inner:ident ],
+            forward: [ $($forward:ident)* ], convert: [ $( [ $convert:ident $Target:ty ] )* ],
+            impls: [ $($auto:ident)* ], owned: $owned:tt }
+    ) => (
+
+        $crate::create_gal_wrapper_type!{
+            @struct [ $(#[$attr])* ] [ $v ] $Type [ $($decl)* ] $cell $Inner [ $($iarg),* ]
+            [ $( [ $(#[$field_attr])* $field_v $field: $field_ty ] )* ]
+        }
+
+        impl<$($params)*> $Type<$($args)*> {
+
+            /// Create a new "bound" instance of this type.
+            ///
+            /// This will lift the lifetime from the inner type to the `Bound` wrapper,
+            /// wrapping the inner type into this type while erasing it's lifetime
+            $v fn $new<$lt>(value: $Inner<$lt $(, $iarg)*> $(, $field: $field_ty)*) -> $crate::Bound<$lt, Self> {
+                use std::mem::{self, ManuallyDrop};
+
+                $crate::__trace_event!("new", $Type);
+                let cell = ManuallyDrop::new(value);
+                $crate::unsafe_block! {
+                    "same mem layout, Bound keeps the wrong lifetime in check" => {
+                        let static_cell = mem::transmute::<
+                            ManuallyDrop<$Inner<$lt $(, $iarg)*>>,
+                            ManuallyDrop<$Inner<'static $(, $iarg)*>>
+                        >(cell);
+                        $crate::Bound::new(Self { $cell: static_cell $(, $field)* })
+                    }
+                }
+            }
+
+            $crate::create_gal_wrapper_type!{
+                @fn_try_new $try_new [ $v ] $new $lt $Inner<$lt $(, $iarg)*> [ $( [ $field ] [ $field_ty ] )* ]
+            }
+
+            $crate::create_gal_wrapper_type!{ @fn_get $get [ $v ] $Inner [ $($iarg),* ] $cell }
+
+            $crate::create_gal_wrapper_type!{ @fn_get_mut $get_mut [ $v ] $Inner [ $($iarg),* ] $cell }
+
+            #[allow(unused)]
+            $v fn $into_inner<'s>(me: $crate::Bound<'s, Self>) -> $Inner<'s $(, $iarg)*> {
+                use std::mem::{self, ManuallyDrop};
+
+                $crate::__trace_event!("into_inner", $Type);
+                $crate::Bound::assert_valid(&me);
+                let static_cell = me._into_inner().$cell;
+
+                let non_static_cell = $crate::unsafe_block! {
+                    "the $Inner<'static> originally had been a $Inner<'s>" => {
+                        mem::transmute::<
+                            ManuallyDrop<$Inner<'static $(, $iarg)*>>,
+                            ManuallyDrop<$Inner<'s $(, $iarg)*>>
+                        >(static_cell)
+                    }
+                };
+
+                ManuallyDrop::into_inner(non_static_cell)
+            }
+
+            $(
+                /// Converts the inner value into the one of another wrapper type (using
+                /// the method with the same name) keeping it bound to the same lifetime.
+                $v fn $convert<'s>(me: $crate::Bound<'s, Self>) -> $crate::Bound<'s, $Target> {
+                    <$Target as $crate::Rebind<'s>>::bind(Self::$into_inner(me).$convert())
+                }
+            )*
+        }
+
+        $crate::create_gal_wrapper_type!{
+            @rebind $Type [ $($params)* ] [ $($args)* ], $Inner [ $($iarg),* ], $new, $into_inner,
+            [ $($field)* ]
+        }
+
+        $crate::create_gal_wrapper_type!{
+            @forwards [ $($forward)* ] $Type [ $($params)* ] [ $($args)* ], $Inner [ $($iarg),* ], $get, $get_mut
+        }
+
+        $crate::create_gal_wrapper_type!{ @unsafe_impls [ $($auto)* ] $Type [ $($params)* ] [ $($args)* ] }
+
+        $crate::create_gal_wrapper_type!{
+            @owned $owned [ $v ] $get $Type [ $($params)* ] [ $($args)* ], $Inner [ $($iarg),* ]
+        }
+
+        impl<'a, $($params)*> $crate::BoundExt<'a> for $Type<$($args)*> {
+
+            #[allow(unsafe_code)]
+            unsafe fn pre_drop(me: &mut $crate::Bound<'a, Self>) {
+                use std::mem::ManuallyDrop;
+
+                $crate::__trace_event!("pre_drop", $Type);
+                // Safe due to the constraints of only calling drop after pre_drop
+                let static_ptr: *mut ManuallyDrop<$Inner<'static $(, $iarg)*>> = &mut me._get_mut().$cell;
+                let as_mut = &mut *(static_ptr as *mut ManuallyDrop<$Inner<'a $(, $iarg)*>>);
+                $crate::PanicPolicy::$on_panic.run(stringify!($Type), || {
+                    $crate::create_gal_wrapper_type!{ @drop_inner as_mut, $Inner<'a $(, $iarg)*>, $on_drop }
+                });
+                $crate::Bound::mark_pre_dropped(me);
+            }
+        }
+
+    );
+
+    (@rebind $Type:ident [ $($params:tt)* ] [ $($args:tt)* ], $Inner:ident [ $($iarg:ty),* ],
+        $new:ident, $into_inner:ident, []
+    ) => (
+        impl<'a, $($params)*> $crate::Rebind<'a> for $Type<$($args)*> {
+            type Inner = $Inner<'a $(, $iarg)*>;
+
+            fn unbind(me: $crate::Bound<'a, Self>) -> $Inner<'a $(, $iarg)*> {
+                Self::$into_inner(me)
+            }
+
+            fn bind(inner: $Inner<'a $(, $iarg)*>) -> $crate::Bound<'a, Self> {
+                Self::$new(inner)
+            }
+        }
+    );
+
+    // `bind` can't provide the other fields
+    (@rebind $Type:ident $params:tt $args:tt, $Inner:ident $iargs:tt, $new:ident, $into_inner:ident,
+        [ $($field:ident)+ ]
+    ) => ();
+
+    (@forwards [] $($_ignored:tt)*) => ();
+
+    (@forwards [ $forward:ident $($rest:ident)* ] $($wrapper:tt)*) => (
+        $crate::create_gal_wrapper_type!{ @forward $forward $($wrapper)* }
+        $crate::create_gal_wrapper_type!{ @forwards [ $($rest)* ] $($wrapper)* }
+    );
+
+    (@forward AsRef $Type:ident [ $($params:tt)* ] [ $($args:tt)* ], $Inner:ident [ $($iarg:ty),* ],
+        $get:ident, $get_mut:tt
+    ) => (
+        impl<'a, $($params)*> ::std::convert::AsRef<$Inner<'a $(, $iarg)*>>
+            for $crate::Bound<'a, $Type<$($args)*>>
+        {
+            fn as_ref(&self) -> &$Inner<'a $(, $iarg)*> {
+                <$Type<$($args)*>>::$get(self)
+            }
+        }
+    );
+
+    (@forward Borrow $Type:ident [ $($params:tt)* ] [ $($args:tt)* ], $Inner:ident [ $($iarg:ty),* ],
+        $get:ident, $get_mut:tt
+    ) => (
+        impl<'a, $($params)*> ::std::borrow::Borrow<$Inner<'a $(, $iarg)*>>
+            for $crate::Bound<'a, $Type<$($args)*>>
+        {
+            fn borrow(&self) -> &$Inner<'a $(, $iarg)*> {
+                <$Type<$($args)*>>::$get(self)
+            }
+        }
+    );
+
+    (@forward AsMut $Type:ident [ $($params:tt)* ] [ $($args:tt)* ], $Inner:ident [ $($iarg:ty),* ],
+        $get:tt, $get_mut:ident
+    ) => (
+        impl<'a, $($params)*> ::std::convert::AsMut<$Inner<'a $(, $iarg)*>>
+            for $crate::Bound<'a, $Type<$($args)*>>
+        {
+            fn as_mut(&mut self) -> &mut $Inner<'a $(, $iarg)*> {
+                <$Type<$($args)*>>::$get_mut(self)
+            }
+        }
+    );
+
+    (@forward Serialize $Type:ident $params:tt $args:tt, $Inner:ident $iargs:tt, $get:ident, $get_mut:tt) => (
+        $crate::__forward_serialize!{ $Type $params $args, $Inner $iargs, $get }
+    );
+
+    (@forward $trait:ident $($_ignored:tt)*) => (
+        compile_error!(concat!(
+            "can not forward `", stringify!($trait), "`, only `AsRef`, `Borrow`, `AsMut` and ",
+            "`Serialize` are supported and they require `get` respectively `get_mut` to not ",
+            "be suppressed"
+        ));
+    );
+
+    (@owned [] $($_ignored:tt)*) => ();
+
+    (@owned [ $Owned:ty ] [ $v:vis ] _ $($_ignored:tt)*) => (
+        compile_error!("`owned` requires `get` to not be suppressed");
+    );
+
+    (@owned [ $Owned:ty ] [ $v:vis ] $get:ident $Type:ident [ $($params:tt)* ] [ $($args:tt)* ],
+        $Inner:ident [ $($iarg:ty),* ]
+    ) => (
+        impl<$($params)*> $Type<$($args)*> {
+
+            /// Copies the inner value into it's owned counterpart, which isn't bound to any lifetime.
+            #[allow(unused)]
+            $v fn to_owned<'s>(me: &$crate::Bound<'s, Self>) -> $Owned
+                where $Owned: for<'r> ::std::convert::From<&'r $Inner<'s $(, $iarg)*>>
+            {
+                <$Owned as ::std::convert::From<&$Inner<'s $(, $iarg)*>>>::from(Self::$get(me))
+            }
+
+            /// Creates a bound instance borrowing from a owned value.
+            #[allow(unused)]
+            $v fn from_owned<'s>(owned: &'s $Owned) -> $crate::Bound<'s, Self>
+                where $Inner<'s $(, $iarg)*>: ::std::convert::From<&'s $Owned>
+            {
+                <Self as $crate::Rebind<'s>>::bind(::std::convert::From::from(owned))
+            }
+        }
+    );
+
+    (@unsafe_impls [] $($_ignored:tt)*) => ();
+
+    (@unsafe_impls [ Send $($rest:ident)* ] $Type:ident [ $($params:tt)* ] [ $($args:tt)* ]) => (
+        // opted in by the user writing `unsafe impl Send;`
+        #[allow(unsafe_code)]
+        unsafe impl<$($params)*> ::std::marker::Send for $Type<$($args)*> {}
+
+        $crate::create_gal_wrapper_type!{ @unsafe_impls [ $($rest)* ] $Type [ $($params)* ] [ $($args)* ] }
+    );
+
+    (@unsafe_impls [ Sync $($rest:ident)* ] $Type:ident [ $($params:tt)* ] [ $($args:tt)* ]) => (
+        // opted in by the user writing `unsafe impl Sync;`
+        #[allow(unsafe_code)]
+        unsafe impl<$($params)*> ::std::marker::Sync for $Type<$($args)*> {}
+
+        $crate::create_gal_wrapper_type!{ @unsafe_impls [ $($rest)* ] $Type [ $($params)* ] [ $($args)* ] }
+    );
+
+    (@unsafe_impls [ $auto:ident $($_ignored:tt)* ] $($_wrapper:tt)*) => (
+        compile_error!(concat!(
+            "can not implement `", stringify!($auto), "`, only `Send` and `Sync` are supported"
+        ));
+    );
+
+    // without extra fields the wrapper has the same layout as the wrapped type
+    (@struct [ $(#[$attr:meta])* ] [ $v:vis ] $Type:ident [ $($decl:tt)* ] $cell:ident
+        $Inner:ident [ $($iarg:ty),* ] []
+    ) => (
+        $(#[$attr])*
+        #[repr(transparent)]
+        $v struct $Type<$($decl)*> {
+            $cell: ::std::mem::ManuallyDrop<$Inner<'static $(, $iarg)*>>
+        }
+    );
+
+    (@struct [ $(#[$attr:meta])* ] [ $v:vis ] $Type:ident [ $($decl:tt)* ] $cell:ident
+        $Inner:ident [ $($iarg:ty),* ] [ $( [ $(#[$field_attr:meta])* $field_v:vis $field:ident: $field_ty:ty ] )+ ]
+    ) => (
+        $(#[$attr])*
+        $v struct $Type<$($decl)*> {
+            $cell: ::std::mem::ManuallyDrop<$Inner<'static $(, $iarg)*>>,
+            $( $(#[$field_attr])* $field_v $field: $field_ty, )+
+        }
+    );
+
+    (@fn_try_new _ $($_ignored:tt)*) => ();
+
+    (@fn_try_new $try_new:ident [ $v:vis ] $new:ident $lt:tt $Inner:ty
+        [ $( [ $field:ident ] [ $field_ty:ty ] )* ]
+    ) => (
+        /// Create a new "bound" instance from the result of a fallible constructor.
+        ///
+        /// This is a shortcut for `result.map(Self::new)`, making it easy to wrap
+        /// constructors which return a `Result`.
+        #[allow(unused)]
+        $v fn $try_new<$lt, E>(
+            result: ::std::result::Result<$Inner, E> $(, $field: $field_ty)*
+        ) -> ::std::result::Result<$crate::Bound<$lt, Self>, E> {
+            result.map(|value| Self::$new(value $(, $field)*))
+        }
+    );
+
+    (@fn_get _ $($_ignored:tt)*) => ();
+
+    (@fn_get $get:ident [ $v:vis ] $Inner:ident [ $($iarg:ty),* ] $cell:ident) => (
+        #[allow(unused)]
+        $v fn $get<'s: 'b, 'b>(me: &'b $crate::Bound<'s, Self>) -> &'b $Inner<'s $(, $iarg)*> {
+            let ptr = &*me.$cell as *const $Inner<'static $(, $iarg)*> as *const $Inner<'s $(, $iarg)*>;
+            $crate::unsafe_block! {
+                "Self was transmuted from $Inner and `'s` is valid due to Bound's guarantees" => {
+                    &*ptr
+                }
+            }
+        }
+    );
+
+    (@fn_get_mut _ $($_ignored:tt)*) => ();
+
+    (@fn_get_mut $get_mut:ident [ $v:vis ] $Inner:ident [ $($iarg:ty),* ] $cell:ident) => (
+        #[allow(unused)]
+        $v fn $get_mut<'s: 'b, 'b>(me: &'b mut $crate::Bound<'s, Self>) -> &'b mut $Inner<'s $(, $iarg)*> {
+            $crate::unsafe_block! {
+                "Self was transmuted from $Inner and `'s` is valid due to Bound's guarantees" => {
+                    // derive the pointer from unique access to self, not through Deref
+                    let ptr = &mut *me._get_mut().$cell as *mut $Inner<'static $(, $iarg)*>
+                        as *mut $Inner<'s $(, $iarg)*>;
+                    &mut *ptr
+                }
+            }
+        }
+    );
+
+    (@drop_inner $cell:ident, $Inner:ty, {}) => (
+        ::std::mem::ManuallyDrop::drop($cell)
+    );
+
+    (@drop_inner $cell:ident, $Inner:ty, { $on_drop:expr }) => ({
+        // the cell is not touched again after the value was moved out of it
+        let value = ::std::ptr::read(&**$cell);
+        let on_drop: fn($Inner) = $on_drop;
+        on_drop(value)
+    });
+
+    // collects the generic parameters of the wrapper as `[ definition ] [ impl ] [ arguments ]`
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ] > $($rest:tt)*) => (
+        $crate::create_gal_wrapper_type!{ @wrapped $head [ $($decl)* ] [ $($params)* ] [ $($args)* ] $($rest)* }
+    );
+
+    (@generics $head:tt $decl:tt $params:tt $args:tt , $($rest:tt)*) => (
+        $crate::create_gal_wrapper_type!{ @generics $head $decl $params $args $($rest)* }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        const $N:ident: $NTy:ty = $default:tt $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head [ $($decl)* const $N: $NTy = $default, ] [ $($params)* const $N: $NTy, ]
+                [ $($args)* $N, ] $($rest)*
+        }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        const $N:ident: $NTy:ty, $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head [ $($decl)* const $N: $NTy, ] [ $($params)* const $N: $NTy, ]
+                [ $($args)* $N, ] $($rest)*
+        }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        const $N:ident: $NTy:ty > $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head [ $($decl)* const $N: $NTy, ] [ $($params)* const $N: $NTy, ]
+                [ $($args)* $N, ] > $($rest)*
+        }
+    );
+
+    // type parameters are `'static` as the wrapper has to be `'static`, too
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        $T:ident $(: $($bound:ident)::+ $(+ $($bounds:ident)::+)*)* = $default:ty, $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head
+                [ $($decl)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)* = $default, ]
+                [ $($params)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($args)* $T, ] $($rest)*
+        }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        $T:ident $(: $($bound:ident)::+ $(+ $($bounds:ident)::+)*)* = $default:ty > $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head
+                [ $($decl)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)* = $default, ]
+                [ $($params)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($args)* $T, ] > $($rest)*
+        }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        $T:ident $(: $($bound:ident)::+ $(+ $($bounds:ident)::+)*)*, $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head
+                [ $($decl)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($params)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($args)* $T, ] $($rest)*
+        }
+    );
+
+    (@generics $head:tt [ $($decl:tt)* ] [ $($params:tt)* ] [ $($args:tt)* ]
+        $T:ident $(: $($bound:ident)::+ $(+ $($bounds:ident)::+)*)* > $($rest:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @generics $head
+                [ $($decl)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($params)* $T: 'static $(+ $($bound)::+ $(+ $($bounds)::+)*)*, ]
+                [ $($args)* $T, ] > $($rest)*
+        }
+    );
+
+    (@wrapped [ [ $(#[$attr:meta])* ] [ $v:vis ] [ $Type:ident ] ] $decl:tt $params:tt $args:tt
+        {
+            #[bound] $cell:ident: $Inner:ident<$lt:tt $(, $iarg:ty)*>
+            $(, $(#[$field_attr:meta])* $field_v:vis $field:ident: $field_ty:ty)* $(,)*
+        } $($options:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options [ [ $(#[$attr])* ] [ $v ] [ $Type ] [ $Inner ] [ $lt ]
+                [ $cell ] [ $( [ [ $(#[$field_attr])* ] [ $field_v ] [ $field ] [ $field_ty ] ] )* ]
+                [ $($iarg),* ] $decl $params $args ]
+            {
+                on_drop: {}, on_panic: Resume, names: [ new try_new get get_mut into_inner ],
+                forward: [], convert: [], impls: [], owned: []
+            }
+            $($options)*
+        }
+    );
+
+    (@wrapped [ [ $(#[$attr:meta])* ] [ $v:vis ] [ $Type:ident ] ] $decl:tt $params:tt $args:tt
+        ($Inner:ident<$lt:tt $(, $iarg:ty)*>); $($options:tt)*
+    ) => (
+        $crate::create_gal_wrapper_type!{
+            @options [ [ $(#[$attr])* ] [ $v ] [ $Type ] [ $Inner ] [ $lt ] [ static_cell ] []
+                [ $($iarg),* ] $decl $params $args ]
+            {
+                on_drop: {}, on_panic: Resume, names: [ new try_new get get_mut into_inner ],
+                forward: [], convert: [], impls: [], owned: []
+            }
+            $($options)*
+        }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident (
+        $(#[$inner_attr:meta])* $inner_v:vis enum $Inner:ident<$lt:tt> { $($body:tt)* }
+    ); $($options:tt)* ) => (
+        $(#[$inner_attr])*
+        $inner_v enum $Inner<$lt> { $($body)* }
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Inner<$lt>); $($options)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident (
+        $(#[$inner_attr:meta])* $inner_v:vis struct $Inner:ident<$lt:tt> { $($body:tt)* }
+    ); $($options:tt)* ) => (
+        $(#[$inner_attr])*
+        $inner_v struct $Inner<$lt> { $($body)* }
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Inner<$lt>); $($options)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident (
+        $(#[$inner_attr:meta])* $inner_v:vis struct $Inner:ident<$lt:tt> ( $($body:tt)* )
+    ); $($options:tt)* ) => (
+        $(#[$inner_attr])*
+        $inner_v struct $Inner<$lt> ( $($body)* );
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Inner<$lt>); $($options)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident (
+        $(#[$alias_attr:meta])* $alias_v:vis type $Alias:ident<$lt:tt> = dyn $($bounds:tt)+
+    ); $($options:tt)* ) => (
+        $(#[$alias_attr])*
+        $alias_v type $Alias<$lt> = ::std::boxed::Box<dyn $($bounds)+>;
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Alias<$lt>); $($options)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident (
+        $(#[$alias_attr:meta])* $alias_v:vis type $Alias:ident<$lt:tt> = $Inner:ty
+    ); $($options:tt)* ) => (
+        $(#[$alias_attr])*
+        $alias_v type $Alias<$lt> = $Inner;
+
+        $crate::create_gal_wrapper_type!{ $(#[$attr])* $v struct $Type($Alias<$lt>); $($options)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident < $($rest:tt)* ) => (
+        $crate::create_gal_wrapper_type!{ @generics [ [ $(#[$attr])* ] [ $v ] [ $Type ] ] [] [] [] $($rest)* }
+    );
+
+    ( $(#[$attr:meta])* $v:vis struct $Type:ident $($rest:tt)* ) => (
+        $crate::create_gal_wrapper_type!{ @wrapped [ [ $(#[$attr])* ] [ $v ] [ $Type ] ] [] [] [] $($rest)* }
+    );
+}
+
+/// Projects a borrowed bound value to a field of it's inner value.
+///
+/// `bound_project!(&Wrapper(bound).field)` is a shorthand for
+/// `Bound::project(&bound, |me| &Wrapper::get(me).field)`, the same
+/// works with `&mut` using `Bound::project_mut` and `Wrapper::get_mut`.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::Bound;
+///
+/// struct Connection { queries: usize }
+/// struct Transaction<'conn> { pub conn: &'conn mut Connection }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// fn count_query<'b>(conn: Bound<'b, &'b mut &mut Connection>) {
+///     conn._into_inner().queries += 1;
+/// }
+///
+/// # fn main() {
+/// let mut conn = Connection { queries: 0 };
+/// {
+///     let mut trans = TransWrap::new(Transaction { conn: &mut conn });
+///     count_query(bound_project!(&mut TransWrap(trans).conn));
+/// }
+/// assert_eq!(conn.queries, 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bound_project {
+    (&mut $Wrap:ident($bound:expr).$field:ident) => (
+        $crate::Bound::project_mut(&mut $bound, |me| &mut $Wrap::get_mut(me).$field)
+    );
+    (&$Wrap:ident($bound:expr).$field:ident) => (
+        $crate::Bound::project(&$bound, |me| &$Wrap::get(me).$field)
+    );
+}
+
+
+/// Compile time checks that wrappers have no size overhead and keep the niche of the
+/// inner type, outside of `mod test` so every build checks them.
+#[allow(dead_code)]
+mod layout {
+    use std::mem;
+
+    struct Ref<'a>(&'a u8);
+
+    create_gal_wrapper_type!{ struct RefWrap(Ref<'a>); }
+
+    const _SAME_SIZE: [(); mem::size_of::<Ref<'static>>()] = [(); mem::size_of::<RefWrap>()];
+    #[cfg(not(all(feature = "validity_checks", debug_assertions)))]
+    const _NICHE_KEPT: [(); mem::size_of::<&u8>()] = [(); mem::size_of::<Option<::Bound<'static, RefWrap>>>()];
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::{Cell, RefCell}, panic};
+    use super::*;
+
+    struct Connection {
+        count: usize
+    }
+
+    struct Transaction<'conn> {
+        conn: &'conn mut Connection
+    }
+
+    impl Connection {
+        fn transaction(&mut self) -> Transaction<'_> {
+            Transaction { conn: self }
+        }
+
+        fn rollback_transaction(&mut self) -> Bound<'_, RollbackTransWrap> {
+            RollbackTransWrap::new(self.transaction())
+        }
+
+        fn try_transaction(&mut self, fail: bool) -> Result<Transaction<'_>, &'static str> {
+            if fail {
+                Err("can not create transaction")
+            } else {
+                Ok(self.transaction())
+            }
+        }
+    }
+
+    trait GCon {
+        type Transaction: GTran;
+
+        fn create_transaction(&mut self) -> Bound<'_, Self::Transaction>;
+    }
+
+    trait GTran: for<'s> BoundExt<'s> {
+        fn commit<'s>(me: Bound<'s, Self>);
+        fn abort<'s>(me: Bound<'s, Self>);
+    }
+
+    create_gal_wrapper_type!{
+        /// Wraps `Transaction` erasing it's lifetime.
+        ///
+        /// This can only be used through a `Bound<'a, TransWrap>` instance,
+        /// as only then it is possible to access the wrapped type with the
+        /// correct lifetime
+        struct TransWrap(Transaction<'a>);
+    }
+
+    impl<'conn> Transaction<'conn> {
+        fn rollback(self) {
+            self.conn.count += 100;
+        }
+    }
+
+    create_gal_wrapper_type!{
+        struct RollbackTransWrap(Transaction<'a>);
+        on_drop = |trans| trans.rollback();
+    }
+
+    struct Exploding<'a> {
+        drops: &'a Cell<usize>
+    }
+
+    impl<'a> Drop for Exploding<'a> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            panic!("exploded");
+        }
+    }
+
+    create_gal_wrapper_type!{
+        struct ExplodingWrap(Exploding<'a>);
+    }
+
+    create_gal_wrapper_type!{
+        struct SwallowingWrap(Exploding<'a>);
+        on_panic = Swallow;
+    }
+
+    impl GCon for Connection {
+        type Transaction = TransWrap;
+
+        fn create_transaction(&mut self) -> Bound<'_, Self::Transaction> {
+            let transaction = self.transaction();
+            TransWrap::new(transaction)
+        }
+    }
+
+    impl GTran for TransWrap {
+        fn commit<'s>(me: Bound<'s, Self>) {
+            let trans = TransWrap::into_inner(me);
+            trans.conn.count += 10;
+        }
+
+        fn abort<'s>(me: Bound<'s, Self>) {
+            let trans = TransWrap::into_inner(me);
+            trans.conn.count += 3;
+        }
+    }
+
+    fn create_commit_generic(x: &mut impl GCon) {
         let trans = x.create_transaction();
         // Using arbitrary self types this can become `trans.commit()`.
         // (extension traits for `Bound<T> where T: GTran` are also an option here).
@@ -542,4 +2088,546 @@ mod test {
         }
         assert_eq!(conn.count, 17)
     }
+
+    #[test]
+    fn it_can_be_created_fallibly() {
+        let mut conn = Connection { count: 0 };
+        {
+            let res = TransWrap::try_new(conn.try_transaction(true));
+            assert_eq!(res.err(), Some("can not create transaction"));
+        }
+        {
+            let trans = TransWrap::try_new(conn.try_transaction(false)).unwrap();
+            GTran::commit(trans);
+        }
+        assert_eq!(conn.count, 10)
+    }
+
+    #[test]
+    fn it_can_be_rebound() {
+        let mut conn1 = Connection { count: 0 };
+        let mut conn2 = Connection { count: 0 };
+        {
+            let trans = conn1.create_transaction();
+            let trans = Bound::rebind(trans, |old| {
+                old.conn.count += 1;
+                conn2.transaction()
+            });
+            GTran::commit(trans);
+        }
+        assert_eq!(conn1.count, 1);
+        assert_eq!(conn2.count, 10);
+    }
+
+    #[test]
+    fn it_can_be_scoped() {
+        let mut conn = Connection { count: 0 };
+        let count = with_bound(&mut conn, |conn| TransWrap::new(conn.transaction()), |trans| {
+            Bound::scope(trans, |trans| {
+                trans.conn.count += 3;
+                trans.conn.count
+            })
+        });
+        assert_eq!(count, 3);
+        assert_eq!(conn.count, 3);
+    }
+
+    #[test]
+    fn panics_in_pre_drop_do_not_double_drop() {
+        let drops = Cell::new(0);
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _bound = ExplodingWrap::new(Exploding { drops: &drops });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn panics_in_pre_drop_can_be_swallowed() {
+        let drops = Cell::new(0);
+        {
+            let _bound = SwallowingWrap::new(Exploding { drops: &drops });
+        }
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn forget_does_not_run_pre_drop() {
+        let mut conn = Connection { count: 0 };
+        {
+            let trans = conn.rollback_transaction();
+            Bound::forget(trans);
+        }
+        assert_eq!(conn.count, 0);
+    }
+
+    #[test]
+    fn on_drop_is_only_called_when_dropped() {
+        let mut conn = Connection { count: 0 };
+        {
+            let _trans = conn.rollback_transaction();
+        }
+        assert_eq!(conn.count, 100);
+        {
+            let trans = conn.rollback_transaction();
+            let trans = RollbackTransWrap::into_inner(trans);
+            trans.conn.count += 1;
+        }
+        assert_eq!(conn.count, 101);
+    }
+
+    #[test]
+    fn zipped_values_are_pre_dropped() {
+        let mut conn1 = Connection { count: 0 };
+        let mut conn2 = Connection { count: 0 };
+        {
+            let _both = Bound::zip(conn1.rollback_transaction(), conn2.rollback_transaction());
+        }
+        assert_eq!((conn1.count, conn2.count), (100, 100));
+        {
+            let both = Bound::zip(conn1.rollback_transaction(), conn2.rollback_transaction());
+            let (_first, second) = Bound::unzip(both);
+            RollbackTransWrap::into_inner(second).conn.count += 1;
+        }
+        assert_eq!((conn1.count, conn2.count), (200, 101));
+    }
+
+    #[test]
+    fn bound_values_can_be_swapped_and_replaced() {
+        let mut conn1 = Connection { count: 0 };
+        let mut conn2 = Connection { count: 0 };
+        let mut conn3 = Connection { count: 0 };
+        {
+            let mut first = conn1.rollback_transaction();
+            let mut second = conn2.rollback_transaction();
+            Bound::swap(&mut first, &mut second);
+            RollbackTransWrap::get_mut(&mut first).conn.count += 1;
+            let old = Bound::replace(&mut second, conn3.rollback_transaction());
+            RollbackTransWrap::into_inner(old).conn.count += 2;
+        }
+        assert_eq!((conn1.count, conn2.count, conn3.count), (2, 101, 100));
+    }
+
+    #[test]
+    fn boxed_values_are_pre_dropped() {
+        let mut conn = Connection { count: 0 };
+        {
+            let _boxed = Bound::boxed(conn.rollback_transaction());
+        }
+        assert_eq!(conn.count, 100);
+        {
+            let boxed = Bound::boxed(conn.rollback_transaction());
+            let _trans = Bound::unbox(boxed);
+        }
+        assert_eq!(conn.count, 200);
+    }
+
+    trait Count {
+        fn add(&mut self, n: usize);
+    }
+
+    impl<'conn> Count for Transaction<'conn> {
+        fn add(&mut self, n: usize) {
+            self.conn.count += n;
+        }
+    }
+
+    type DynCount<'a> = Box<dyn Count + 'a>;
+
+    create_gal_wrapper_type!{ struct DynCountWrap(DynCount<'a>); }
+
+    #[test]
+    fn it_can_be_erased() {
+        let mut conn = Connection { count: 0 };
+        {
+            let trans = TransWrap::new(conn.transaction());
+            let mut erased = Bound::erase(trans, |trans| Box::new(trans) as DynCount);
+            DynCountWrap::get_mut(&mut erased).add(3);
+        }
+        assert_eq!(conn.count, 3);
+    }
+
+    create_gal_wrapper_type!{
+        struct InlineWrap(
+            struct Inline<'a> {
+                conn: &'a mut Connection,
+                added: usize
+            }
+        );
+        on_drop = |inline| inline.conn.count += inline.added;
+    }
+
+    #[test]
+    fn inline_definitions_can_be_wrapped() {
+        let mut conn = Connection { count: 0 };
+        {
+            let mut inline = InlineWrap::new(Inline { conn: &mut conn, added: 1 });
+            InlineWrap::get_mut(&mut inline).added += 1;
+        }
+        assert_eq!(conn.count, 2);
+    }
+
+    create_gal_wrapper_type!{
+        struct CountWrap(type AnyCount<'a> = dyn Count + 'a);
+        on_drop = |mut count| count.add(100);
+    }
+
+    create_gal_wrapper_type!{
+        struct SendCountWrap(type SendCount<'a> = Box<dyn Count + Send + 'a>);
+        forward = [AsMut];
+    }
+
+    #[test]
+    fn trait_objects_can_be_wrapped() {
+        let mut conn = Connection { count: 0 };
+        {
+            let mut count: Bound<CountWrap> = CountWrap::new(Box::new(conn.transaction()));
+            CountWrap::get_mut(&mut count).add(1);
+        }
+        assert_eq!(conn.count, 101);
+        {
+            let mut count = SendCountWrap::new(Box::new(conn.transaction()));
+            AsMut::<SendCount>::as_mut(&mut count).add(2);
+            let count: AnyCount = SendCountWrap::into_inner(count);
+            let _count = CountWrap::new(count);
+        }
+        assert_eq!(conn.count, 203);
+    }
+
+    create_gal_wrapper_type!{
+        struct CountingTransWrap {
+            #[bound] trans: Transaction<'a>,
+            pub label: &'static str,
+            statements: usize,
+        }
+        on_drop = |trans| trans.rollback();
+    }
+
+    #[test]
+    fn it_can_have_extra_fields() {
+        let mut conn = Connection { count: 0 };
+        {
+            let trans = CountingTransWrap::new(conn.transaction(), "trans", 2);
+            assert_eq!(trans.label, "trans");
+            assert_eq!(trans.statements, 2);
+        }
+        assert_eq!(conn.count, 100);
+        {
+            let trans = CountingTransWrap::try_new(conn.try_transaction(false), "trans", 0).unwrap();
+            CountingTransWrap::into_inner(trans).conn.count += 1;
+        }
+        assert_eq!(conn.count, 101);
+    }
+
+    struct Logged<'a>(&'a RefCell<Vec<&'static str>>, &'static str);
+
+    impl<'a> Drop for Logged<'a> {
+        fn drop(&mut self) {
+            self.0.borrow_mut().push(self.1);
+        }
+    }
+
+    create_gal_wrapper_type!{ struct LoggedPairWrap(struct LoggedPair<'a>(Logged<'a>, Logged<'a>)); }
+
+    #[test]
+    fn inline_fields_are_dropped_in_order() {
+        let log = RefCell::new(Vec::new());
+        {
+            let pair = LoggedPairWrap::new(LoggedPair(Logged(&log, "first"), Logged(&log, "second")));
+            let LoggedPair(first, second) = LoggedPairWrap::get(&pair);
+            assert_eq!((first.1, second.1), ("first", "second"));
+        }
+        assert_eq!(*log.borrow(), ["first", "second"]);
+    }
+
+    create_gal_wrapper_type!{
+        struct RenamedWrap(Transaction<'a>);
+        rename = { new: gal_new, try_new: _, get: _, get_mut: gal_get_mut, into_inner: gal_into_inner, };
+        on_drop = |trans| trans.rollback();
+    }
+
+    impl RenamedWrap {
+        fn new(conn: &mut Connection) -> Bound<'_, Self> {
+            Self::gal_new(conn.transaction())
+        }
+    }
+
+    #[test]
+    fn generated_functions_can_be_renamed() {
+        let mut conn = Connection { count: 0 };
+        {
+            let mut trans = RenamedWrap::new(&mut conn);
+            RenamedWrap::gal_get_mut(&mut trans).conn.count += 1;
+        }
+        assert_eq!(conn.count, 101);
+        {
+            let trans = RenamedWrap::new(&mut conn);
+            Bound::scope(trans, |trans| trans.conn.count += 1);
+        }
+        assert_eq!(conn.count, 102);
+        RenamedWrap::gal_into_inner(RenamedWrap::new(&mut conn)).conn.count += 1;
+        assert_eq!(conn.count, 103);
+    }
+
+    create_gal_wrapper_type! {
+        struct ForwardingWrap(Transaction<'a>);
+        rename = { get: gal_get };
+        forward = [AsRef, Borrow];
+        forward = [AsMut];
+    }
+
+    #[test]
+    fn accessors_can_be_forwarded() {
+        use std::borrow::Borrow;
+
+        fn count<'a, T: Borrow<Transaction<'a>>>(trans: &T) -> usize {
+            trans.borrow().conn.count
+        }
+
+        let mut conn = Connection { count: 0 };
+        {
+            let mut trans = ForwardingWrap::new(conn.transaction());
+            AsMut::<Transaction>::as_mut(&mut trans).conn.count += 2;
+            assert_eq!(count(&trans), 2);
+            assert_eq!(AsRef::<Transaction>::as_ref(&trans).conn.count, 2);
+        }
+        assert_eq!(conn.count, 2);
+    }
+
+    struct Savepoint<'conn> {
+        trans: Transaction<'conn>
+    }
+
+    impl<'conn> Transaction<'conn> {
+        fn into_savepoint(self) -> Savepoint<'conn> {
+            Savepoint { trans: self }
+        }
+    }
+
+    create_gal_wrapper_type! {
+        struct SavepointWrap(Savepoint<'a>);
+        on_drop = |savepoint| savepoint.trans.conn.count += 10;
+    }
+
+    create_gal_wrapper_type! {
+        struct ConvertibleWrap(Transaction<'a>);
+        on_drop = |trans| trans.conn.count += 100;
+        convert fn into_savepoint -> SavepointWrap;
+    }
+
+    #[test]
+    fn wrappers_can_be_converted() {
+        let mut conn = Connection { count: 0 };
+        {
+            let trans = ConvertibleWrap::new(conn.transaction());
+            let mut savepoint = ConvertibleWrap::into_savepoint(trans);
+            SavepointWrap::get_mut(&mut savepoint).trans.conn.count += 1;
+        }
+        assert_eq!(conn.count, 11);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct TransactionState {
+        count: usize
+    }
+
+    impl<'a, 'conn> From<&'a Transaction<'conn>> for TransactionState {
+        fn from(trans: &'a Transaction<'conn>) -> Self {
+            TransactionState { count: trans.conn.count }
+        }
+    }
+
+    struct Name<'a>(&'a str);
+
+    impl<'a, 'b> From<&'a Name<'b>> for String {
+        fn from(name: &'a Name<'b>) -> Self {
+            name.0.to_owned()
+        }
+    }
+
+    impl<'a> From<&'a String> for Name<'a> {
+        fn from(name: &'a String) -> Self {
+            Name(name)
+        }
+    }
+
+    create_gal_wrapper_type! {
+        struct NameWrap(Name<'a>);
+        owned = String;
+    }
+
+    create_gal_wrapper_type! {
+        struct SnapshotWrap(Transaction<'a>);
+        owned = TransactionState;
+    }
+
+    #[test]
+    fn bound_values_can_be_converted_to_and_from_owned_values() {
+        let owned = {
+            let source = String::from("row");
+            let name = NameWrap::new(Name(&source));
+            NameWrap::to_owned(&name)
+        };
+        assert_eq!(NameWrap::get(&NameWrap::from_owned(&owned)).0, "row");
+
+        let mut conn = Connection { count: 3 };
+        let state = SnapshotWrap::to_owned(&SnapshotWrap::new(conn.transaction()));
+        assert_eq!(state, TransactionState { count: 3 });
+    }
+
+    struct ChunkReader<'a, T, const N: usize> {
+        source: &'a [T],
+        chunk: [T; N]
+    }
+
+    impl<'a, T: Copy + Default, const N: usize> ChunkReader<'a, T, N> {
+        fn next_chunk(&mut self) -> Option<&[T]> {
+            if self.source.is_empty() {
+                return None;
+            }
+            let len = self.source.len().min(N);
+            self.chunk[..len].copy_from_slice(&self.source[..len]);
+            self.source = &self.source[len..];
+            Some(&self.chunk[..len])
+        }
+    }
+
+    create_gal_wrapper_type! {
+        struct ChunkReaderWrap<const N: usize, T: Copy + Default = u8>(ChunkReader<'a, T, N>);
+        forward = [AsMut];
+    }
+
+    create_gal_wrapper_type! {
+        struct LabeledChunkReaderWrap<const N: usize> {
+            #[bound] reader: ChunkReader<'a, u32, N>,
+            label: &'static str
+        }
+    }
+
+    #[test]
+    fn wrapped_types_can_have_const_and_type_parameters() {
+        let data = vec![1, 2, 3, 4, 5];
+        let mut reader: Bound<ChunkReaderWrap<2>> = ChunkReaderWrap::new(ChunkReader { source: &data, chunk: [0; 2] });
+        assert_eq!(reader.as_mut().next_chunk(), Some(&[1, 2][..]));
+        let mut inner = ChunkReaderWrap::into_inner(reader);
+        assert_eq!(inner.next_chunk(), Some(&[3, 4][..]));
+
+        let values = [7u32; 4];
+        let mut labeled = LabeledChunkReaderWrap::<3>::new(ChunkReader { source: &values, chunk: [0; 3] }, "sevens");
+        let mut chunks = 0;
+        while LabeledChunkReaderWrap::get_mut(&mut labeled).next_chunk().is_some() {
+            chunks += 1;
+        }
+        assert_eq!((labeled.label, chunks), ("sevens", 2));
+    }
+
+    #[cfg(feature = "serde")]
+    struct Row<'a> { name: &'a str }
+
+    #[cfg(feature = "serde")]
+    impl<'a> __serde::Serialize for Row<'a> {
+        fn serialize<S: __serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(self.name)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    create_gal_wrapper_type! {
+        struct RowWrap(Row<'a>);
+        forward = [Serialize];
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_can_be_forwarded() {
+        extern crate serde_json;
+
+        let name = String::from("alice");
+        let row = RowWrap::new(Row { name: &name });
+        assert_eq!(serde_json::to_string(&row).unwrap(), "\"alice\"");
+    }
+
+    #[test]
+    fn plain_references_can_be_bound() {
+        let mut count = 0;
+        {
+            let shared: Bound<&usize> = Bound::from(&count);
+            assert_eq!(**shared, 0);
+        }
+        {
+            let exclusive: Bound<&mut usize> = Bound::from(&mut count);
+            let label: Bound<&str> = Bound::from("label");
+            let exclusive = Bound::zip(exclusive, label);
+            let (count, label) = exclusive._into_inner();
+            *count += label.len();
+        }
+        assert_eq!(count, 5);
+    }
+
+    /// Owns heap memory, so that misuse (e.g. double drops) is detected by Miri.
+    struct Buffered<'a> {
+        buffer: Vec<u8>,
+        target: &'a mut Vec<u8>
+    }
+
+    impl<'a> Drop for Buffered<'a> {
+        fn drop(&mut self) {
+            self.target.append(&mut self.buffer);
+        }
+    }
+
+    create_gal_wrapper_type!{ struct BufferedWrap(Buffered<'a>); }
+
+    // run with `cargo +nightly miri test` to check the unsafe code
+    #[test]
+    fn heap_owning_values_can_be_used() {
+        let mut target = Vec::new();
+        {
+            let mut bound = BufferedWrap::new(Buffered { buffer: vec![1], target: &mut target });
+            BufferedWrap::get_mut(&mut bound).buffer.push(2);
+            assert_eq!(BufferedWrap::get(&bound).buffer, [1, 2]);
+        }
+        {
+            let bound = BufferedWrap::new(Buffered { buffer: vec![3], target: &mut target });
+            let bound = Bound::boxed(bound);
+            let mut inner = BufferedWrap::into_inner(Bound::unbox(bound));
+            inner.buffer.push(4);
+        }
+        assert_eq!(target, [1, 2, 3, 4]);
+    }
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn wrappers_are_sync_if_the_inner_type_is() {
+        assert_sync::<TransWrap>();
+        assert_sync::<Bound<TransWrap>>();
+    }
+
+    #[test]
+    fn fields_can_be_projected() {
+        let mut conn = Connection { count: 0 };
+        {
+            let mut trans = TransWrap::new(conn.transaction());
+            {
+                let count = Bound::project_mut(&mut trans, |me| &mut TransWrap::get_mut(me).conn.count);
+                *count._into_inner() += 2;
+            }
+            let conn = bound_project!(&TransWrap(trans).conn);
+            assert_eq!(conn.count, 2);
+        }
+        assert_eq!(conn.count, 2);
+    }
+
+    #[test]
+    fn zipped_values_can_be_rebound() {
+        let mut conn1 = Connection { count: 0 };
+        let mut conn2 = Connection { count: 0 };
+        let both = Bound::zip(TransWrap::new(conn1.transaction()), TransWrap::new(conn2.transaction()));
+        Bound::scope(both, |(first, second)| {
+            first.conn.count += 1;
+            second.conn.count += 2;
+        });
+        assert_eq!((conn1.count, conn2.count), (1, 2));
+    }
 }
\ No newline at end of file
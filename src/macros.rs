@@ -1,12 +1,103 @@
 
 
-#[doc(hidden)]
+/// Runs a `unsafe` block, requiring a reason why it is safe.
+///
+/// This is what galemu uses internally and it is meant to be used in hand-written
+/// wrappers, too. Together with `#![deny(unsafe_code)]` it makes sure every unsafe
+/// block comes with a (greppable) explanation. The reason is kept as a constant
+/// (`UNSAFE_REASON`) in the expansion, so it's visible e.g. in `cargo expand`.
+///
+/// With the `audit_unsafe` feature the reason and location of every executed unsafe
+/// block are passed to the hook set with `galemu::audit::set_hook()` in builds with
+/// debug assertions.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// # fn main() {
+/// let values = [1, 2, 3];
+/// let last = unsafe_block! {
+///     "the array is not empty" => {
+///         *values.get_unchecked(values.len() - 1)
+///     }
+/// };
+/// assert_eq!(last, 3);
+/// # }
+/// ```
 #[macro_export]
 macro_rules! unsafe_block {
     ($why:tt => $code:block) => ({
-        #[allow(unsafe_code)]
+        #[allow(dead_code)]
+        const UNSAFE_REASON: &'static str = $why;
+        $crate::__audit_unsafe!(UNSAFE_REASON);
+        // expanding `$code` in a unsafe block is the whole point
+        #[allow(unsafe_code, clippy::macro_metavars_in_unsafe)]
         unsafe {
             $code
         }
     });
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "audit_unsafe")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_unsafe {
+    ($why:expr) => (
+        if cfg!(debug_assertions) {
+            $crate::audit::record(&$crate::audit::UnsafeUsage {
+                reason: $why,
+                file: file!(),
+                line: line!(),
+                column: column!()
+            });
+        }
+    );
+}
+
+#[cfg(not(feature = "audit_unsafe"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __audit_unsafe {
+    ($why:expr) => ();
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_serialize {
+    ($Type:ident [ $($params:tt)* ] [ $($args:tt)* ], $Inner:ident [ $($iarg:ty),* ], $get:ident) => (
+        impl<'a, $($params)*> $crate::__serde::Serialize for $crate::Bound<'a, $Type<$($args)*>>
+            where $Inner<'a $(, $iarg)*>: $crate::__serde::Serialize
+        {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                where S: $crate::__serde::Serializer
+            {
+                $crate::__serde::Serialize::serialize(<$Type<$($args)*>>::$get(self), serializer)
+            }
+        }
+    );
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_serialize {
+    ($($_ignored:tt)*) => (
+        compile_error!("forwarding `Serialize` requires the `serde` feature of galemu");
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trace_event {
+    ($event:expr, $Type:ident) => (
+        $crate::trace::wrapper_event($event, stringify!($Type))
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trace_event {
+    ($($_ignored:tt)*) => ();
+}
@@ -0,0 +1,350 @@
+//! A small migration runner generic over [`traits::Connection`](crate::traits::Connection).
+//!
+//! [`Migrations`] is a list of steps, each a version and a function applying the
+//! step to a bound transaction. Which versions were already applied is tracked by
+//! a [`VersionStore`] supplied by the user, e.g. a table read with a
+//! [`GeneralCursor`](crate::cursor::GeneralCursor) and written in the same
+//! transaction as the step.
+//!
+//! [`Migrations::run()`] applies every pending step in it's own transaction,
+//! [`Migrations::dry_run()`] applies them all in one transaction which is rolled
+//! back afterwards:
+//!
+//! ```
+//! # #[macro_use] extern crate galemu;
+//! use galemu::Bound;
+//! use galemu::access::{AccessMode, ReadWrite};
+//! use galemu::migrate::{Migrations, VersionStore};
+//! use galemu::traits::{Connection, Transaction};
+//!
+//! #[derive(Default)]
+//! struct Db { tables: Vec<&'static str>, versions: Vec<u64> }
+//! struct Trans<'a> { db: &'a mut Db, staged: Db }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+//!
+//! struct Versions;
+//!
+//! impl VersionStore<TransWrap> for Versions {
+//!     type Error = ();
+//!
+//!     fn applied(&mut self, trans: &mut Bound<'_, TransWrap>) -> Result<Vec<u64>, ()> {
+//!         Ok(TransWrap::get(trans).staged.versions.clone())
+//!     }
+//!
+//!     fn record(&mut self, trans: &mut Bound<'_, TransWrap>, version: u64, _name: &str) -> Result<(), ()> {
+//!         TransWrap::get_mut(trans).staged.versions.push(version);
+//!         Ok(())
+//!     }
+//! }
+//! # impl Connection for Db {
+//! #     type Error = ();
+//! #     type Transaction = TransWrap;
+//! #     fn begin(&mut self) -> Result<Bound<'_, TransWrap>, ()> {
+//! #         let staged = Db { tables: self.tables.clone(), versions: self.versions.clone() };
+//! #         Ok(TransWrap::new(Trans { db: self, staged }))
+//! #     }
+//! # }
+//! # impl AccessMode for TransWrap { type Access = ReadWrite; }
+//! # impl Transaction for TransWrap {
+//! #     type Error = ();
+//! #     fn commit(me: Bound<'_, Self>) -> Result<(), ()> {
+//! #         let trans = TransWrap::into_inner(me);
+//! #         *trans.db = trans.staged;
+//! #         Ok(())
+//! #     }
+//! #     fn rollback(_me: Bound<'_, Self>) -> Result<(), ()> { Ok(()) }
+//! # }
+//!
+//! fn create_users(trans: &mut Bound<'_, TransWrap>) -> Result<(), ()> {
+//!     TransWrap::get_mut(trans).staged.tables.push("users");
+//!     Ok(())
+//! }
+//!
+//! # fn main() {
+//! let migrations = Migrations::new()
+//!     .add(1, "create users", create_users)
+//!     .add(2, "create posts", |trans| {
+//!         TransWrap::get_mut(trans).staged.tables.push("posts");
+//!         Ok(())
+//!     });
+//!
+//! let mut db = Db::default();
+//! assert_eq!(migrations.dry_run(&mut db, &mut Versions), Ok(vec![1, 2]));
+//! assert!(db.tables.is_empty());
+//! assert_eq!(migrations.run(&mut db, &mut Versions), Ok(vec![1, 2]));
+//! assert_eq!(db.tables, ["users", "posts"]);
+//! assert_eq!(migrations.run(&mut db, &mut Versions), Ok(vec![]));
+//! # }
+//! ```
+use Bound;
+use traits::{Connection, Transaction};
+
+/// Tracks the versions of the applied migration steps.
+///
+/// Both methods are called with the transaction the step is applied in, so
+/// recording the version is committed (or rolled back) together with it.
+pub trait VersionStore<T: Transaction> {
+    /// The error returned by the store.
+    type Error;
+
+    /// Returns the versions of all applied steps.
+    fn applied(&mut self, trans: &mut Bound<'_, T>) -> Result<Vec<u64>, Self::Error>;
+
+    /// Records the step as applied.
+    fn record(&mut self, trans: &mut Bound<'_, T>, version: u64, name: &str) -> Result<(), Self::Error>;
+}
+
+/// A migration step, see [`Migrations::add()`].
+pub struct Migration<T, E> {
+    version: u64,
+    name: &'static str,
+    apply: fn(&mut Bound<'_, T>) -> Result<(), E>
+}
+
+impl<T, E> Migration<T, E> {
+
+    /// Returns the version of the step.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the name of the step.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// A ordered list of migration steps for transactions of type `T`.
+pub struct Migrations<T, E> {
+    steps: Vec<Migration<T, E>>
+}
+
+impl<T, E> Default for Migrations<T, E> {
+    fn default() -> Self {
+        Migrations { steps: Vec::new() }
+    }
+}
+
+impl<T, E> Migrations<T, E>
+    where T: Transaction
+{
+
+    /// Creates a empty list of steps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step applied by `apply`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` isn't greater than the version of the previous step.
+    pub fn add(mut self, version: u64, name: &'static str, apply: fn(&mut Bound<'_, T>) -> Result<(), E>) -> Self {
+        if let Some(last) = self.steps.last() {
+            assert!(version > last.version, "migration versions have to increase ({} after {})", version, last.version);
+        }
+        self.steps.push(Migration { version, name, apply });
+        self
+    }
+
+    /// Returns the steps.
+    pub fn steps(&self) -> &[Migration<T, E>] {
+        &self.steps
+    }
+
+    /// Applies the pending steps, each in a new transaction, and returns their versions.
+    ///
+    /// The applied versions are read in a transaction of their own. Each step is
+    /// recorded in the transaction it's applied in, which is committed afterwards.
+    /// If a step fails it's transaction is rolled back and the error returned, the
+    /// steps before it stay applied.
+    pub fn run<C, S>(&self, conn: &mut C, store: &mut S) -> Result<Vec<u64>, E>
+        where C: Connection<Transaction=T>, T: Transaction<Error=C::Error>,
+              S: VersionStore<T>, E: From<C::Error> + From<S::Error>
+    {
+        let applied = {
+            let mut trans = conn.begin()?;
+            let applied = store.applied(&mut trans);
+            Transaction::commit(trans)?;
+            applied?
+        };
+        let mut done = Vec::new();
+        for step in self.pending(&applied) {
+            let mut trans = conn.begin()?;
+            if let Err(err) = Self::apply(step, &mut trans, store) {
+                let _ = Transaction::rollback(trans);
+                return Err(err);
+            }
+            Transaction::commit(trans)?;
+            done.push(step.version);
+        }
+        Ok(done)
+    }
+
+    /// Applies the pending steps in one transaction which is rolled back, returns their versions.
+    ///
+    /// This checks that the steps can be applied (including recording them) without
+    /// changing anything. All steps share one transaction, as later steps normally
+    /// depend on the earlier ones.
+    pub fn dry_run<C, S>(&self, conn: &mut C, store: &mut S) -> Result<Vec<u64>, E>
+        where C: Connection<Transaction=T>, T: Transaction<Error=C::Error>,
+              S: VersionStore<T>, E: From<C::Error> + From<S::Error>
+    {
+        let mut trans = conn.begin()?;
+        let res = self.apply_all(&mut trans, store);
+        let rollback = Transaction::rollback(trans);
+        let done = res?;
+        rollback?;
+        Ok(done)
+    }
+
+    fn apply_all<S>(&self, trans: &mut Bound<'_, T>, store: &mut S) -> Result<Vec<u64>, E>
+        where S: VersionStore<T>, E: From<S::Error>
+    {
+        let applied = store.applied(trans)?;
+        let mut done = Vec::new();
+        for step in self.pending(&applied) {
+            Self::apply(step, trans, store)?;
+            done.push(step.version);
+        }
+        Ok(done)
+    }
+
+    fn apply<S>(step: &Migration<T, E>, trans: &mut Bound<'_, T>, store: &mut S) -> Result<(), E>
+        where S: VersionStore<T>, E: From<S::Error>
+    {
+        (step.apply)(trans)?;
+        store.record(trans, step.version, step.name)?;
+        Ok(())
+    }
+
+    fn pending<'s>(&'s self, applied: &'s [u64]) -> impl Iterator<Item=&'s Migration<T, E>> + 's {
+        self.steps.iter().filter(move |step| !applied.contains(&step.version))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use create_gal_wrapper_type;
+    use access::{AccessMode, ReadWrite};
+    use super::*;
+
+    #[derive(Clone, Default, Debug, PartialEq)]
+    struct Db {
+        tables: Vec<&'static str>,
+        versions: Vec<(u64, String)>,
+        commits: usize
+    }
+
+    /// Changes `staged`, which replaces the database on commit.
+    struct Trans<'a> {
+        db: &'a mut Db,
+        staged: Db
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+
+    impl Connection for Db {
+        type Error = &'static str;
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> Result<Bound<'_, TransWrap>, &'static str> {
+            let staged = self.clone();
+            Ok(TransWrap::new(Trans { db: self, staged }))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl Transaction for TransWrap {
+        type Error = &'static str;
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            let trans = TransWrap::into_inner(me);
+            *trans.db = trans.staged;
+            trans.db.commits += 1;
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    struct Versions;
+
+    impl VersionStore<TransWrap> for Versions {
+        type Error = &'static str;
+
+        fn applied(&mut self, trans: &mut Bound<'_, TransWrap>) -> Result<Vec<u64>, &'static str> {
+            Ok(TransWrap::get(trans).staged.versions.iter().map(|&(version, _)| version).collect())
+        }
+
+        fn record(&mut self, trans: &mut Bound<'_, TransWrap>, version: u64, name: &str) -> Result<(), &'static str> {
+            TransWrap::get_mut(trans).staged.versions.push((version, name.to_owned()));
+            Ok(())
+        }
+    }
+
+    fn create(trans: &mut Bound<'_, TransWrap>, table: &'static str) -> Result<(), &'static str> {
+        let tables = &mut TransWrap::get_mut(trans).staged.tables;
+        if tables.contains(&table) {
+            return Err("table exists");
+        }
+        tables.push(table);
+        Ok(())
+    }
+
+    fn migrations() -> Migrations<TransWrap, &'static str> {
+        Migrations::new()
+            .add(1, "users", |trans| create(trans, "users"))
+            .add(3, "posts", |trans| create(trans, "posts"))
+    }
+
+    #[test]
+    fn each_pending_step_is_applied_in_a_new_transaction() {
+        let mut db = Db::default();
+        assert_eq!(migrations().run(&mut db, &mut Versions), Ok(vec![1, 3]));
+        assert_eq!(db.tables, ["users", "posts"]);
+        assert_eq!(db.versions, [(1, "users".to_owned()), (3, "posts".to_owned())]);
+        // one for reading the versions and one per step
+        assert_eq!(db.commits, 3);
+
+        let migrations = migrations().add(4, "comments", |trans| create(trans, "comments"));
+        assert_eq!(migrations.run(&mut db, &mut Versions), Ok(vec![4]));
+        assert_eq!(db.tables, ["users", "posts", "comments"]);
+        assert_eq!(db.versions.last(), Some(&(4, "comments".to_owned())));
+        assert_eq!(migrations.run(&mut db, &mut Versions), Ok(vec![]));
+    }
+
+    #[test]
+    fn failing_steps_are_rolled_back() {
+        let mut db = Db::default();
+        let migrations = migrations().add(4, "users again", |trans| create(trans, "users"));
+        assert_eq!(migrations.run(&mut db, &mut Versions), Err("table exists"));
+        assert_eq!(db.tables, ["users", "posts"]);
+        assert_eq!(db.versions, [(1, "users".to_owned()), (3, "posts".to_owned())]);
+    }
+
+    #[test]
+    fn dry_runs_change_nothing() {
+        let mut db = Db::default();
+        assert_eq!(migrations().dry_run(&mut db, &mut Versions), Ok(vec![1, 3]));
+        assert_eq!(db, Db::default());
+
+        let migrations = migrations().add(4, "users again", |trans| create(trans, "users"));
+        assert_eq!(migrations.dry_run(&mut db, &mut Versions), Err("table exists"));
+        assert_eq!(db, Db::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "migration versions have to increase (3 after 3)")]
+    fn versions_have_to_increase() {
+        migrations().add(3, "again", |_| Ok(()));
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    mem::{self, ManuallyDrop},
+    ptr
+};
+
+use {Bound, BoundExt};
+
+/// Bundles a owner (e.g. a connection) with a value bound to it (e.g. a transaction).
+///
+/// A `Bound<'a, T>` borrows from it's owner, which makes it impossible to e.g.
+/// return a connection together with it's open transaction from a function. `Owned`
+/// moves the owner into a `Box` and binds the value to the boxed owner, so both
+/// can be moved around as one value.
+///
+/// # Access
+///
+/// The bound value can only be accessed through closures which are generic over the
+/// lifetime of the `Bound` (e.g. [`Owned::with_mut()`]). Handing out a `Bound<'s, T>`
+/// with a lifetime `'s` chosen by the caller would allow swapping it with a `Bound`
+/// which is only valid for `'s` but not for the lifetime of the owner.
+///
+/// # Drop
+///
+/// The bound value is always dropped (incl. [`BoundExt::pre_drop()`]) before the owner.
+///
+/// # Example
+///
+/// ```
+/// use galemu::{Bound, Owned, create_gal_wrapper_type};
+///
+/// struct Connection { count: usize }
+///
+/// struct Transaction<'conn> { conn: &'conn mut Connection }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// fn open() -> Owned<Connection, TransWrap> {
+///     let conn = Connection { count: 0 };
+///     Owned::new(conn, |conn| TransWrap::new(Transaction { conn }))
+/// }
+///
+/// let mut owned = open();
+/// owned.with_mut(|trans| TransWrap::get_mut(trans).conn.count += 1);
+/// let conn = owned.into_owner();
+/// assert_eq!(conn.count, 1);
+/// ```
+pub struct Owned<O, T>
+    where T: for<'a> BoundExt<'a>
+{
+    // the lifetime is erased, it actually is bound to `*owner`
+    bound: ManuallyDrop<Bound<'static, T>>,
+    // created through `Box::into_raw`, owned by this instance
+    owner: *mut O
+}
+
+impl<O, T> Owned<O, T>
+    where T: for<'a> BoundExt<'a>
+{
+
+    /// Moves `owner` into a box and binds the value created by `make` to it.
+    pub fn new<F>(owner: O, make: F) -> Self
+        where F: for<'a> FnOnce(&'a mut O) -> Bound<'a, T>
+    {
+        match Self::try_new(owner, |owner| Ok::<_, ()>(make(owner))) {
+            Ok(owned) => owned,
+            Err(()) => unreachable!()
+        }
+    }
+
+    /// Like [`Owned::new()`] but for a fallible `make` function.
+    ///
+    /// If `make` fails the owner is dropped and the error is returned.
+    pub fn try_new<F, E>(owner: O, make: F) -> Result<Self, E>
+        where F: for<'a> FnOnce(&'a mut O) -> Result<Bound<'a, T>, E>
+    {
+        // Note: if `make` panics the owner is leaked, which is safe.
+        let owner = Box::into_raw(Box::new(owner));
+        let res = unsafe_block! {
+            "the pointer was just created from a box and is not aliased" => {
+                make(&mut *owner)
+            }
+        };
+        match res {
+            Ok(bound) => {
+                let bound = unsafe_block! {
+                    "only the lifetime changes, Owned makes sure owner outlives bound" => {
+                        mem::transmute::<Bound<T>, Bound<'static, T>>(bound)
+                    }
+                };
+                Ok(Owned { bound: ManuallyDrop::new(bound), owner })
+            },
+            Err(err) => {
+                drop(unsafe_block! {
+                    "the pointer was created through Box::into_raw and is no longer borrowed" => {
+                        Box::from_raw(owner)
+                    }
+                });
+                Err(err)
+            }
+        }
+    }
+
+    /// Calls `func` with a shared reference to the bound value.
+    pub fn with<F, R>(&self, func: F) -> R
+        where F: for<'a> FnOnce(&Bound<'a, T>) -> R
+    {
+        func(&*self.bound)
+    }
+
+    /// Calls `func` with a mutable reference to the bound value.
+    pub fn with_mut<F, R>(&mut self, func: F) -> R
+        where F: for<'a> FnOnce(&mut Bound<'a, T>) -> R
+    {
+        func(&mut *self.bound)
+    }
+
+    /// Consumes the bound value with `func` and returns the result and the owner.
+    ///
+    /// E.g. this can be used to commit a transaction and get back the connection.
+    pub fn consume<F, R>(self, func: F) -> (R, O)
+        where F: for<'a> FnOnce(Bound<'a, T>) -> R
+    {
+        let (bound, owner) = self.into_raw_parts();
+        let res = func(bound);
+        let owner = unsafe_block! {
+            "the bound value was consumed and the pointer was created through Box::into_raw" => {
+                Box::from_raw(owner)
+            }
+        };
+        (res, *owner)
+    }
+
+    /// Drops the bound value and returns the owner.
+    pub fn into_owner(self) -> O {
+        let ((), owner) = self.consume(|bound| drop(bound));
+        owner
+    }
+
+    fn into_raw_parts(self) -> (Bound<'static, T>, *mut O) {
+        let me = ManuallyDrop::new(self);
+        let bound = unsafe_block! {
+            "me is not dropped so bound is moved out only once" => {
+                ptr::read(&*me.bound)
+            }
+        };
+        (bound, me.owner)
+    }
+}
+
+impl<O, T> Drop for Owned<O, T>
+    where T: for<'a> BoundExt<'a>
+{
+    fn drop(&mut self) {
+        unsafe_block! {
+            "bound is dropped before the owner it borrows from and neither is used afterwards" => {
+                ManuallyDrop::drop(&mut self.bound);
+                drop(Box::from_raw(self.owner));
+            }
+        }
+    }
+}
+
+// The owner is only accessed through the bound value, so Owned behaves like a (O, T).
+#[allow(unsafe_code)]
+unsafe impl<O, T> Send for Owned<O, T>
+    where O: Send, T: Send + for<'a> BoundExt<'a> {}
+
+// &Owned only gives access to &Bound<T>, the owner is not accessible.
+#[allow(unsafe_code)]
+unsafe impl<O, T> Sync for Owned<O, T>
+    where T: Sync + for<'a> BoundExt<'a> {}
+
+
+#[cfg(test)]
+mod test {
+    use {Bound, create_gal_wrapper_type};
+    use super::*;
+
+    struct Connection {
+        count: usize
+    }
+
+    struct Transaction<'conn> {
+        conn: &'conn mut Connection
+    }
+
+    impl<'conn> Drop for Transaction<'conn> {
+        fn drop(&mut self) {
+            self.conn.count += 100;
+        }
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+
+    fn open(count: usize) -> Owned<Connection, TransWrap> {
+        Owned::new(Connection { count }, |conn| TransWrap::new(Transaction { conn }))
+    }
+
+    #[test]
+    fn owner_and_bound_value_can_be_moved_together() {
+        let owned = open(1);
+        let mut moved = Box::new(owned);
+        let count = moved.with_mut(|trans| {
+            let trans = TransWrap::get_mut(trans);
+            trans.conn.count += 1;
+            trans.conn.count
+        });
+        assert_eq!(count, 2);
+        assert_eq!(moved.with(|trans| TransWrap::get(trans).conn.count), 2);
+    }
+
+    #[test]
+    fn bound_value_is_dropped_before_owner() {
+        let conn = open(0).into_owner();
+        assert_eq!(conn.count, 100);
+    }
+
+    #[test]
+    fn bound_value_can_be_consumed() {
+        let (count, conn) = open(0).consume(|trans: Bound<TransWrap>| {
+            let trans = TransWrap::into_inner(trans);
+            trans.conn.count += 1;
+            trans.conn.count
+        });
+        assert_eq!(count, 1);
+        assert_eq!(conn.count, 101);
+    }
+
+    #[test]
+    fn failing_construction_returns_the_error() {
+        let res = Owned::<_, TransWrap>::try_new(Connection { count: 0 }, |_| Err("failed"));
+        assert_eq!(res.err(), Some("failed"));
+    }
+}
@@ -0,0 +1,368 @@
+//! Keyset pagination on top of the [`cursor`](crate::cursor) module.
+//!
+//! With keyset pagination each page is fetched by a query returning the rows
+//! ordered by a (unique) key, starting after the key of the last row of the
+//! previous page, e.g. `SELECT .. WHERE id > ?1 ORDER BY id LIMIT ?2`. Cursors of
+//! such queries implement [`KeysetCursor`], which tells how to get the key of a
+//! row. [`KeysetCursor::page()`] returns a [`Page`] view of the cursor, which
+//! yields at most one page of rows (bound to the cursor, as usual) and afterwards
+//! returns the [`PageToken`] to continue with. The token owns the key, so it can
+//! outlive the transaction the page was read in (e.g. be sent to a client), and
+//! [`for_each_page()`] reads every page in a new transaction:
+//!
+//! ```
+//! # #[macro_use] extern crate galemu;
+//! use galemu::{Bound, cursor::GeneralCursor};
+//! use galemu::access::{AccessMode, ReadWrite};
+//! use galemu::page::{self, KeysetCursor};
+//! use galemu::traits::{Connection, Transaction};
+//!
+//! struct Table { ids: Vec<u32> }
+//! struct Trans<'a> { table: &'a Table }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+//!
+//! // the cursor of `SELECT id FROM table WHERE id > ?1 ORDER BY id LIMIT ?2`
+//! struct Rows<'t> { ids: std::slice::Iter<'t, u32> }
+//! struct Row<'t> { id: &'t u32 }
+//!
+//! impl<'t> Rows<'t> {
+//!     fn query(trans: &Trans<'t>, after: Option<&u32>, limit: usize) -> Self {
+//!         let start = after.map_or(0, |after| trans.table.ids.iter().take_while(|id| *id <= after).count());
+//!         let end = trans.table.ids.len().min(start + limit);
+//!         Rows { ids: trans.table.ids[start..end].iter() }
+//!     }
+//!
+//!     fn next(&mut self) -> Result<Option<Row<'t>>, ()> {
+//!         Ok(self.ids.next().map(|id| Row { id }))
+//!     }
+//! }
+//!
+//! create_gal_row_type! {
+//!     struct RowWrap(Row<'a>);
+//!
+//!     impl<'t> GeneralCursor for Rows<'t> {
+//!         type Error = ();
+//!         next_row = Rows::next;
+//!         columns = |_| vec!["id"];
+//!     }
+//! }
+//!
+//! impl<'t> KeysetCursor for Rows<'t> {
+//!     type Key = u32;
+//!
+//!     fn key(row: &Bound<'_, RowWrap>) -> u32 {
+//!         *RowWrap::get(row).id
+//!     }
+//! }
+//! # impl Connection for Table {
+//! #     type Error = ();
+//! #     type Transaction = TransWrap;
+//! #     fn begin(&mut self) -> Result<Bound<'_, TransWrap>, ()> { Ok(TransWrap::new(Trans { table: self })) }
+//! # }
+//! # impl AccessMode for TransWrap { type Access = ReadWrite; }
+//! # impl Transaction for TransWrap {
+//! #     type Error = ();
+//! #     fn commit(_me: Bound<'_, Self>) -> Result<(), ()> { Ok(()) }
+//! #     fn rollback(_me: Bound<'_, Self>) -> Result<(), ()> { Ok(()) }
+//! # }
+//!
+//! # fn main() {
+//! let mut table = Table { ids: vec![1, 3, 4, 7, 9] };
+//! let mut pages = Vec::new();
+//! page::for_each_page(&mut table, None, |trans, after| {
+//!     let mut rows = Rows::query(TransWrap::get(trans), after, 2);
+//!     let mut page = rows.page(2);
+//!     let mut ids = Vec::new();
+//!     page.for_each_row(|row| ids.push(*RowWrap::get(&row).id))?;
+//!     pages.push(ids);
+//!     Ok::<_, ()>(page.into_token())
+//! }).unwrap();
+//! assert_eq!(pages, [vec![1, 3], vec![4, 7], vec![9]]);
+//! # }
+//! ```
+use Bound;
+use cursor::GeneralCursor;
+use traits::{Connection, Transaction};
+
+/// A cursor over rows ordered by a unique key, as used for keyset pagination.
+#[doc(alias = "GPage")]
+pub trait KeysetCursor: GeneralCursor {
+    /// The (owned) key the rows are ordered by.
+    type Key;
+
+    /// Returns the key of the row.
+    fn key(row: &Bound<'_, Self::Row>) -> Self::Key;
+
+    /// Returns a view of the cursor yielding at most `limit` rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    fn page(&mut self, limit: usize) -> Page<'_, Self>
+        where Self: Sized
+    {
+        assert!(limit > 0, "pages have to contain at least one row");
+        Page { cursor: self, limit, read: 0, last: None }
+    }
+}
+
+/// A page of the rows of a [`KeysetCursor`], see [`KeysetCursor::page()`].
+///
+/// It is a cursor itself, which ends after the rows of the page, afterwards
+/// [`Page::into_token()`] returns the token to continue with.
+pub struct Page<'c, C: KeysetCursor + 'c> {
+    cursor: &'c mut C,
+    limit: usize,
+    read: usize,
+    last: Option<C::Key>
+}
+
+impl<'c, C> Page<'c, C>
+    where C: KeysetCursor
+{
+
+    /// Returns the number of rows read so far.
+    pub fn len(&self) -> usize {
+        self.read
+    }
+
+    /// Returns true if no rows were read so far.
+    pub fn is_empty(&self) -> bool {
+        self.read == 0
+    }
+
+    /// Returns the token to continue after the last row read, if the page is full.
+    ///
+    /// If the page isn't full there are no more rows and `None` is returned. As the
+    /// page can't know if a full page was the last one, the page after it might be empty.
+    pub fn into_token(self) -> Option<PageToken<C::Key>> {
+        if self.read < self.limit {
+            return None;
+        }
+        self.last.map(PageToken::new)
+    }
+}
+
+// the row is always `'static` (`for<'a> BoundExt<'a>` requires it), but that isn't inferred
+impl<'c, C> GeneralCursor for Page<'c, C>
+    where C: KeysetCursor, C::Row: 'static
+{
+    type Row = C::Row;
+    type Error = C::Error;
+
+    fn columns(&self) -> Vec<&str> {
+        self.cursor.columns()
+    }
+
+    fn next_row(&mut self) -> Result<Option<Bound<'_, C::Row>>, C::Error> {
+        if self.read == self.limit {
+            return Ok(None);
+        }
+        let row = self.cursor.next_row()?;
+        if let Some(ref row) = row {
+            self.read += 1;
+            self.last = Some(C::key(row));
+        }
+        Ok(row)
+    }
+}
+
+/// Continuation token of a page, i.e. the key of it's last row.
+///
+/// It owns the key, so it's not bound to the transaction (or cursor) the page was
+/// read with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PageToken<K> {
+    after: K
+}
+
+impl<K> PageToken<K> {
+
+    /// Creates a token continuing after the given key.
+    pub fn new(after: K) -> Self {
+        PageToken { after }
+    }
+
+    /// Returns the key the next page starts after.
+    pub fn after(&self) -> &K {
+        &self.after
+    }
+
+    /// Returns the key the next page starts after.
+    pub fn into_after(self) -> K {
+        self.after
+    }
+}
+
+/// Calls `page` for each page, every time with a new transaction begun on `conn`.
+///
+/// `page` is called with the key to start after (`None` for the first page if there is no
+/// `start` token) and has to read the page (e.g. using [`KeysetCursor::page()`]) and return
+/// the token of the next one. Every transaction is committed after reading it's page. The
+/// pages end once `page` returns `None` (or a error, in which case it's transaction is
+/// rolled back).
+pub fn for_each_page<C, K, E, F>(conn: &mut C, start: Option<PageToken<K>>, mut page: F) -> Result<(), E>
+    where C: Connection, E: From<C::Error>,
+          F: FnMut(&mut Bound<'_, C::Transaction>, Option<&K>) -> Result<Option<PageToken<K>>, E>
+{
+    let mut token = start;
+    loop {
+        let mut trans = conn.begin()?;
+        token = match page(&mut trans, token.as_ref().map(PageToken::after)) {
+            Ok(token) => token,
+            Err(err) => {
+                let _ = Transaction::rollback(trans);
+                return Err(err);
+            }
+        };
+        Transaction::commit(trans)?;
+        if token.is_none() {
+            return Ok(());
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use {create_gal_row_type, create_gal_wrapper_type};
+    use access::{AccessMode, ReadWrite};
+    use super::*;
+
+    struct Table {
+        ids: Vec<u32>,
+        commits: usize
+    }
+
+    struct Trans<'a> {
+        table: &'a mut Table
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+
+    impl Connection for Table {
+        type Error = &'static str;
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> Result<Bound<'_, TransWrap>, &'static str> {
+            Ok(TransWrap::new(Trans { table: self }))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl Transaction for TransWrap {
+        type Error = &'static str;
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            TransWrap::into_inner(me).table.commits += 1;
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    /// Rows with ids after `after`, fails on the id 0.
+    struct Rows<'t> {
+        ids: &'t [u32]
+    }
+
+    struct Row<'t> {
+        id: &'t u32
+    }
+
+    impl<'t> Rows<'t> {
+        fn query(trans: &'t Bound<TransWrap>, after: Option<&u32>) -> Self {
+            let ids = &TransWrap::get(trans).table.ids;
+            let start = after.map_or(0, |after| ids.iter().take_while(|id| *id <= after).count());
+            Rows { ids: &ids[start..] }
+        }
+
+        fn next(&mut self) -> Result<Option<Row<'t>>, &'static str> {
+            match self.ids.split_first() {
+                Some((&0, _)) => Err("zero"),
+                Some((id, rest)) => {
+                    self.ids = rest;
+                    Ok(Some(Row { id }))
+                },
+                None => Ok(None)
+            }
+        }
+    }
+
+    create_gal_row_type! {
+        struct RowWrap(Row<'a>);
+
+        impl<'t> GeneralCursor for Rows<'t> {
+            type Error = &'static str;
+            next_row = Rows::next;
+            columns = |_| vec!["id"];
+        }
+    }
+
+    impl<'t> KeysetCursor for Rows<'t> {
+        type Key = u32;
+
+        fn key(row: &Bound<'_, RowWrap>) -> u32 {
+            *RowWrap::get(row).id
+        }
+    }
+
+    fn read_page(trans: &Bound<TransWrap>, after: Option<&u32>, ids: &mut Vec<u32>) -> Result<Option<PageToken<u32>>, &'static str> {
+        let mut rows = Rows::query(trans, after);
+        let mut page = rows.page(2);
+        page.for_each_row(|row| ids.push(*RowWrap::get(&row).id))?;
+        Ok(page.into_token())
+    }
+
+    #[test]
+    fn pages_end_after_their_rows() {
+        let mut table = Table { ids: vec![1, 2, 3], commits: 0 };
+        let trans = table.begin().unwrap();
+        let mut rows = Rows::query(&trans, None);
+        {
+            let mut page = rows.page(2);
+            assert_eq!(page.columns(), &["id"]);
+            assert!(page.is_empty());
+            assert_eq!(RowWrap::get(&page.next_row().unwrap().unwrap()).id, &1);
+            page.for_each_row(|_| ()).unwrap();
+            assert_eq!(page.len(), 2);
+            assert_eq!(page.into_token(), Some(PageToken::new(2)));
+        }
+        let mut page = rows.page(2);
+        page.for_each_row(|_| ()).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.into_token(), None);
+    }
+
+    #[test]
+    fn each_page_is_read_in_a_new_transaction() {
+        let mut table = Table { ids: vec![1, 3, 4, 7], commits: 0 };
+        let mut ids = Vec::new();
+        for_each_page(&mut table, None, |trans, after| read_page(trans, after, &mut ids)).unwrap();
+        assert_eq!(ids, [1, 3, 4, 7]);
+        // the last page is empty, as the one before it is full
+        assert_eq!(table.commits, 3);
+
+        ids.clear();
+        for_each_page(&mut table, Some(PageToken::new(3)), |trans, after| read_page(trans, after, &mut ids)).unwrap();
+        assert_eq!(ids, [4, 7]);
+        assert_eq!(table.commits, 5);
+    }
+
+    #[test]
+    fn failing_pages_are_rolled_back() {
+        let mut table = Table { ids: vec![1, 3, 5, 0], commits: 0 };
+        let mut ids = Vec::new();
+        let res = for_each_page(&mut table, None, |trans, after| read_page(trans, after, &mut ids));
+        assert_eq!(res, Err("zero"));
+        assert_eq!(ids, [1, 3, 5]);
+        assert_eq!(table.commits, 1);
+    }
+}
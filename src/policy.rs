@@ -0,0 +1,83 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    process
+};
+
+/// Determines what happens if dropping the inner value of a `Bound` wrapper panics.
+///
+/// This is used by the [`BoundExt::pre_drop()`](crate::BoundExt::pre_drop) implementation
+/// of types created by [`create_gal_wrapper_type`](crate::create_gal_wrapper_type) and can
+/// be set per wrapper type using the
+/// `on_panic = <variant>;` option.
+///
+/// Independent of the policy the inner value is never dropped twice, it's
+/// wrapped in a `ManuallyDrop` and is considered dropped once dropping it started.
+///
+/// Note that a panic which propagates out of `pre_drop` while the thread is
+/// already unwinding will always abort the process (as with any panicking
+/// `Drop` implementation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Let the panic propagate (re-raise it), this is the default.
+    Resume,
+    /// Abort the process.
+    Abort,
+    /// Catch the panic, print it to stderr and continue normally.
+    Swallow
+}
+
+impl PanicPolicy {
+
+    /// Runs `drop_fn` handling a panic in it according to this policy.
+    ///
+    /// `type_name` is used when reporting swallowed panics.
+    pub fn run<F>(self, type_name: &str, drop_fn: F)
+        where F: FnOnce()
+    {
+        if self == PanicPolicy::Resume {
+            return drop_fn();
+        }
+
+        // the value is considered dropped once drop_fn was called
+        // so there is no broken invariant which could be observed
+        let payload = match panic::catch_unwind(AssertUnwindSafe(drop_fn)) {
+            Ok(()) => return,
+            Err(payload) => payload
+        };
+
+        match self {
+            PanicPolicy::Abort => process::abort(),
+            _ => {
+                let msg = payload.downcast_ref::<&'static str>().cloned()
+                    .or_else(|| payload.downcast_ref::<String>().map(|s| &**s))
+                    .unwrap_or("Box<Any>");
+                eprintln!("galemu: swallowed panic while dropping `{}`: {}", type_name, msg);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::panic;
+    use super::*;
+
+    #[test]
+    fn swallow_catches_the_panic() {
+        let mut called = false;
+        PanicPolicy::Swallow.run("Test", || {
+            called = true;
+            panic!("drop failed");
+        });
+        assert!(called);
+    }
+
+    #[test]
+    fn resume_propagates_the_panic() {
+        let res = panic::catch_unwind(|| {
+            PanicPolicy::Resume.run("Test", || panic!("drop failed"));
+        });
+        assert!(res.is_err());
+    }
+}
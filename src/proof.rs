@@ -0,0 +1,165 @@
+use std::{fmt, marker::PhantomData};
+
+// `fn(&'x ()) -> &'x ()` makes the proofs invariant over their lifetimes, as
+// subtyping a proof could turn it into a proof of something which isn't true.
+type Invariant<'x> = PhantomData<fn(&'x ()) -> &'x ()>;
+
+/// Zero sized proof that `'long` outlives `'short`.
+///
+/// This allows unsafe code to take the relation as argument instead of
+/// relying on reasoning about where it's called from:
+///
+/// ```
+/// use std::mem;
+/// use galemu::Outlives;
+///
+/// fn shorten<'long, 'short>(value: &'long str, _proof: Outlives<'long, 'short>) -> &'short str {
+///     // the proof guarantees that the reference is valid for 'short
+///     unsafe { mem::transmute::<&'long str, &'short str>(value) }
+/// }
+///
+/// let value = "static";
+/// let proof = Outlives::new();
+/// assert_eq!(shorten(value, proof), "static");
+/// ```
+pub struct Outlives<'long, 'short> {
+    long: Invariant<'long>,
+    short: Invariant<'short>
+}
+
+impl<'long, 'short> Outlives<'long, 'short> {
+
+    /// Creates the proof, the compiler checks that `'long: 'short`.
+    pub fn new() -> Self
+        where 'long: 'short
+    {
+        Outlives { long: PhantomData, short: PhantomData }
+    }
+
+    /// Creates the proof without checking it.
+    ///
+    /// # Safety
+    ///
+    /// The caller has to make sure `'long` outlives `'short`, as unsafe code
+    /// taking the proof as argument might rely on it.
+    #[allow(unsafe_code)]
+    pub unsafe fn trusted() -> Self {
+        Outlives { long: PhantomData, short: PhantomData }
+    }
+
+    /// Combines this proof with a proof that `'short` outlives `'shorter`.
+    pub fn trans<'shorter>(self, _other: Outlives<'short, 'shorter>) -> Outlives<'long, 'shorter> {
+        unsafe_block! {
+            "'long: 'short and 'short: 'shorter implies 'long: 'shorter" => {
+                Outlives::trusted()
+            }
+        }
+    }
+}
+
+/// Zero sized proof that `'a` and `'b` are the same lifetime.
+pub struct LtEq<'a, 'b> {
+    a: Invariant<'a>,
+    b: Invariant<'b>
+}
+
+impl<'a> LtEq<'a, 'a> {
+
+    /// Creates the proof that a lifetime is equal to itself.
+    pub fn refl() -> Self {
+        LtEq { a: PhantomData, b: PhantomData }
+    }
+}
+
+impl<'a, 'b> LtEq<'a, 'b> {
+
+    /// Creates the proof without checking it.
+    ///
+    /// # Safety
+    ///
+    /// The caller has to make sure `'a` and `'b` are the same lifetime, as unsafe
+    /// code taking the proof as argument might rely on it.
+    #[allow(unsafe_code)]
+    pub unsafe fn trusted() -> Self {
+        LtEq { a: PhantomData, b: PhantomData }
+    }
+
+    /// Returns the proof with the lifetimes swapped.
+    pub fn symm(self) -> LtEq<'b, 'a> {
+        unsafe_block! {
+            "equality is symmetric" => {
+                LtEq::trusted()
+            }
+        }
+    }
+
+    /// Returns a proof that `'a` outlives `'b` (as they are equal).
+    pub fn outlives(self) -> Outlives<'a, 'b> {
+        unsafe_block! {
+            "every lifetime outlives itself" => {
+                Outlives::trusted()
+            }
+        }
+    }
+}
+
+// manual impls as derives would require the (non-existing) type parameters to implement them
+
+impl<'long: 'short, 'short> Default for Outlives<'long, 'short> {
+    fn default() -> Self {
+        Outlives::new()
+    }
+}
+
+impl<'long, 'short> Clone for Outlives<'long, 'short> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'long, 'short> Copy for Outlives<'long, 'short> {}
+
+impl<'long, 'short> fmt::Debug for Outlives<'long, 'short> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.write_str("Outlives")
+    }
+}
+
+impl<'a, 'b> Clone for LtEq<'a, 'b> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, 'b> Copy for LtEq<'a, 'b> {}
+
+impl<'a, 'b> fmt::Debug for LtEq<'a, 'b> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.write_str("LtEq")
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::mem;
+    use super::*;
+
+    fn shorten<'long, 'short>(value: &'long u8, _proof: Outlives<'long, 'short>) -> &'short u8 {
+        unsafe_block! {
+            "the proof guarantees 'long: 'short" => {
+                mem::transmute::<&'long u8, &'short u8>(value)
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_can_be_combined() {
+        let value = 12;
+        let eq = LtEq::refl();
+        let proof: Outlives<'static, '_> = Outlives::new().trans(eq.symm().outlives());
+        let short: &u8 = shorten(&3, proof);
+        assert_eq!(*short + value, 15);
+        assert_eq!(mem::size_of_val(&proof), 0);
+    }
+}
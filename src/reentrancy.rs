@@ -0,0 +1,216 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt::{self, Display},
+    marker::PhantomData
+};
+
+use {Bound, BoundExt, BoundMut};
+
+/// A slot from which at most one [`Token`] can be taken at a time.
+///
+/// If a connection creates it's transactions through `&mut self` the borrow
+/// checker already prevents starting a second transaction while one is live
+/// (as the returned `Bound<'conn, _>` keeps the mutable borrow alive). But some
+/// backends create transactions through `&self`, in which case nothing stops
+/// a second transaction from being started from the same connection, which
+/// depending on the backend might be anything from an error to a panic to
+/// silently doing the wrong thing.
+///
+/// For this cases the connection can contain a `TokenSlot` and take a token
+/// from it when creating a transaction. The token is stored in the
+/// transaction (i.e. the inner type of the wrapper created with
+/// [`create_gal_wrapper_type`](crate::create_gal_wrapper_type)) and is
+/// returned to the slot when the transaction is dropped, which happens in
+/// `pre_drop`. Trying to create a second transaction while the first one is
+/// live now fails with a well defined [`AlreadyInUse`] error.
+///
+/// If the bound value is forgotten (e.g. with [`Bound::forget`](crate::Bound::forget))
+/// the token is never returned and the slot stays in use.
+///
+/// If the connection itself is a bound value [`TokenMut`] makes this a compile
+/// time error instead.
+///
+/// ```
+/// #[macro_use]
+/// extern crate galemu;
+///
+/// use galemu::{Bound, Token, TokenSlot, AlreadyInUse};
+///
+/// struct Connection { slot: TokenSlot }
+///
+/// struct Transaction<'conn> { _token: Token<'conn> }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// impl Connection {
+///     fn transaction(&self) -> Result<Bound<'_, TransWrap>, AlreadyInUse> {
+///         let _token = self.slot.take()?;
+///         Ok(TransWrap::new(Transaction { _token }))
+///     }
+/// }
+///
+/// fn main() {
+///     let conn = Connection { slot: TokenSlot::new() };
+///     let trans = conn.transaction().unwrap();
+///     assert!(conn.transaction().is_err());
+///     drop(trans);
+///     assert!(conn.transaction().is_ok());
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct TokenSlot {
+    in_use: Cell<bool>
+}
+
+impl TokenSlot {
+
+    /// Creates a new slot which has it's token available.
+    pub fn new() -> Self {
+        TokenSlot { in_use: Cell::new(false) }
+    }
+
+    /// Takes the token from the slot.
+    ///
+    /// # Error
+    ///
+    /// Fails if the token was already taken and not yet returned.
+    pub fn take(&self) -> Result<Token<'_>, AlreadyInUse> {
+        if self.in_use.replace(true) {
+            Err(AlreadyInUse)
+        } else {
+            Ok(Token { slot: self })
+        }
+    }
+
+    /// Returns true if the token is currently taken.
+    pub fn is_in_use(&self) -> bool {
+        self.in_use.get()
+    }
+}
+
+/// Token taken from a [`TokenSlot`], it is returned to the slot when dropped.
+#[derive(Debug)]
+pub struct Token<'a> {
+    slot: &'a TokenSlot
+}
+
+impl<'a> Drop for Token<'a> {
+    fn drop(&mut self) {
+        self.slot.in_use.set(false);
+    }
+}
+
+/// Token for a exclusively borrowed bound owner, the compile time checked variant of [`Token`].
+///
+/// If the owner (e.g. a connection taken from a pool) is a bound value, functions
+/// creating transactions can take a [`BoundMut`] view of it. Taking the token from
+/// the view returns a shared reference to the owner, for backends which create
+/// transactions through `&self`. The owner stays mutably borrowed while the token
+/// or the reference is live, so if both are stored in the transaction the borrow
+/// checker rejects starting a second transaction while the first one is live,
+/// without any runtime state:
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, BoundMut, TokenMut};
+///
+/// struct Connection<'pool> { _pool: &'pool () }
+/// struct Transaction<'conn> { _token: TokenMut<'conn>, conn: &'conn Connection<'conn> }
+///
+/// create_gal_wrapper_type!{ struct ConnWrap(Connection<'a>); }
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// fn transaction<'b>(conn: BoundMut<'b, '_, ConnWrap>) -> Bound<'b, TransWrap> {
+///     let (_token, conn) = TokenMut::take(conn);
+///     TransWrap::new(Transaction { _token, conn: ConnWrap::get(conn) })
+/// }
+///
+/// # fn main() {
+/// let pool = ();
+/// let mut conn = ConnWrap::new(Connection { _pool: &pool });
+/// let trans = transaction(Bound::as_mut(&mut conn));
+/// drop(trans);
+/// let _trans = transaction(Bound::as_mut(&mut conn));
+/// # }
+/// ```
+///
+/// Starting a second transaction while the first one is live doesn't compile:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate galemu;
+/// # use galemu::{Bound, BoundMut, TokenMut};
+/// # struct Connection<'pool> { _pool: &'pool () }
+/// # struct Transaction<'conn> { _token: TokenMut<'conn>, conn: &'conn Connection<'conn> }
+/// # create_gal_wrapper_type!{ struct ConnWrap(Connection<'a>); }
+/// # create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+/// # fn transaction<'b>(conn: BoundMut<'b, '_, ConnWrap>) -> Bound<'b, TransWrap> {
+/// #     let (_token, conn) = TokenMut::take(conn);
+/// #     TransWrap::new(Transaction { _token, conn: ConnWrap::get(conn) })
+/// # }
+/// # fn main() {
+/// let pool = ();
+/// let mut conn = ConnWrap::new(Connection { _pool: &pool });
+/// let trans = transaction(Bound::as_mut(&mut conn));
+/// // error: `conn` is already mutably borrowed by `trans`
+/// let trans2 = transaction(Bound::as_mut(&mut conn));
+/// drop(trans);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TokenMut<'b> {
+    borrow: PhantomData<&'b mut ()>
+}
+
+impl<'b> TokenMut<'b> {
+
+    /// Takes the token from the exclusive view of the owner.
+    ///
+    /// Also returns a shared reference to the owner, both keep it mutably borrowed.
+    pub fn take<'a, T>(owner: BoundMut<'b, 'a, T>) -> (Self, &'b Bound<'a, T>)
+        where T: BoundExt<'a>
+    {
+        (TokenMut { borrow: PhantomData }, BoundMut::into_mut(owner))
+    }
+}
+
+/// Error returned when taking a token from a [`TokenSlot`] which is in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyInUse;
+
+impl Display for AlreadyInUse {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.write_str("owner already has a live bound value")
+    }
+}
+
+impl Error for AlreadyInUse {}
+
+
+#[cfg(test)]
+mod test {
+    use Static;
+    use super::*;
+
+    #[test]
+    fn tokens_are_returned_on_drop() {
+        let slot = TokenSlot::new();
+        let token = slot.take().unwrap();
+        assert!(slot.is_in_use());
+        assert_eq!(slot.take().unwrap_err(), AlreadyInUse);
+        drop(token);
+        assert!(!slot.is_in_use());
+        assert!(slot.take().is_ok());
+    }
+
+    #[test]
+    fn exclusive_tokens_borrow_the_owner() {
+        let mut owner = Bound::new_static(vec![1]);
+        {
+            let (_token, owner) = TokenMut::take(Bound::as_mut(&mut owner));
+            assert_eq!(Static::get(owner), &[1]);
+        }
+        Static::get_mut(&mut owner).push(2);
+        assert_eq!(Static::get(&owner), &[1, 2]);
+    }
+}
@@ -0,0 +1,745 @@
+//! Recording and replaying the operations done through the [`traits`](crate::traits).
+//!
+//! [`Recorder`] wraps a connection and logs every `begin`, `begin_read`, `savepoint`,
+//! `commit` and `rollback` (and transactions dropped without either) done through it,
+//! incl. if the operation succeeded. The [`Log`] can be written as text (one entry per line, or with
+//! the `serde` feature with `serde`) and later be re-executed with [`replay()`] against
+//! a mock or real backend, e.g. to turn a failing transactional flow into a
+//! deterministic regression test. Queries run through backend specific APIs are not
+//! part of the log.
+//!
+//! ```
+//! use galemu::{Bound, create_gal_wrapper_type};
+//! use galemu::access::{AccessMode, ReadWrite};
+//! use galemu::replay::{self, Log, Recorder};
+//! use galemu::traits::{Connection, Transaction, Savepoint};
+//!
+//! struct Store { commits: usize }
+//! struct Trans<'a> { store: &'a mut Store }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+//!
+//! impl Connection for Store {
+//!     type Error = ();
+//!     type Transaction = TransWrap;
+//!
+//!     fn begin(&mut self) -> Result<Bound<'_, TransWrap>, ()> {
+//!         Ok(TransWrap::new(Trans { store: self }))
+//!     }
+//! }
+//!
+//! impl AccessMode for TransWrap {
+//!     type Access = ReadWrite;
+//! }
+//!
+//! impl Transaction for TransWrap {
+//!     type Error = ();
+//!
+//!     fn commit(me: Bound<'_, Self>) -> Result<(), ()> {
+//!         TransWrap::into_inner(me).store.commits += 1;
+//!         Ok(())
+//!     }
+//!
+//!     fn rollback(me: Bound<'_, Self>) -> Result<(), ()> {
+//!         drop(me);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl Savepoint for TransWrap {
+//!     type Savepoint = TransWrap;
+//!
+//!     fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, TransWrap>, ()> {
+//!         Ok(TransWrap::new(Trans { store: TransWrap::get_mut(me).store }))
+//!     }
+//! }
+//!
+//! let mut conn = Recorder::new(Store { commits: 0 });
+//! let mut trans = conn.begin().unwrap();
+//! Transaction::rollback(Savepoint::savepoint(&mut trans).unwrap()).unwrap();
+//! Transaction::commit(trans).unwrap();
+//!
+//! let log = conn.log().to_string();
+//! assert_eq!(log, "begin 0\nsavepoint 0 1\nrollback 1\ncommit 0");
+//!
+//! let mut store = Store { commits: 0 };
+//! replay::replay(&log.parse::<Log>().unwrap(), &mut store).unwrap();
+//! assert_eq!(store.commits, 1);
+//! ```
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    mem::{self, ManuallyDrop},
+    str::FromStr,
+    iter::{Cloned, Enumerate},
+    slice,
+    sync::{Arc, Mutex}
+};
+
+use {Bound, BoundExt};
+use access::{AccessMode, ReadWrite, Writable};
+use traits::{Connection, ReadConnection, Savepoint, Transaction};
+
+/// A operation recorded in a [`Log`].
+///
+/// Transactions and savepoints are identified by a id unique within the log, `ok`
+/// is false if the operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Entry {
+    /// A transaction was begun on the connection.
+    Begin { id: usize, ok: bool },
+    /// A read-only transaction was begun on the connection.
+    BeginRead { id: usize, ok: bool },
+    /// A savepoint was created in the transaction (or savepoint) `parent`.
+    Savepoint { parent: usize, id: usize, ok: bool },
+    /// The transaction (or savepoint) was committed.
+    Commit { id: usize, ok: bool },
+    /// The transaction (or savepoint) was rolled back.
+    Rollback { id: usize, ok: bool },
+    /// The transaction (or savepoint) was dropped without being committed or rolled back.
+    Drop { id: usize }
+}
+
+impl Display for Entry {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let ok = match *self {
+            Entry::Begin { id, ok } => { write!(fter, "begin {}", id)?; ok },
+            Entry::BeginRead { id, ok } => { write!(fter, "begin_read {}", id)?; ok },
+            Entry::Savepoint { parent, id, ok } => { write!(fter, "savepoint {} {}", parent, id)?; ok },
+            Entry::Commit { id, ok } => { write!(fter, "commit {}", id)?; ok },
+            Entry::Rollback { id, ok } => { write!(fter, "rollback {}", id)?; ok },
+            Entry::Drop { id } => { write!(fter, "drop {}", id)?; true }
+        };
+        if !ok {
+            fter.write_str(" failed")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Entry {
+    type Err = ParseLogError;
+
+    fn from_str(line: &str) -> Result<Self, ParseLogError> {
+        let invalid = || ParseLogError { line: line.to_owned() };
+        let mut parts = line.split_whitespace().collect::<Vec<_>>();
+        let ok = parts.last() != Some(&"failed");
+        if !ok {
+            parts.pop();
+        }
+        let ids = parts[1.min(parts.len())..].iter()
+            .map(|id| id.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let entry = match (parts.first().cloned(), &ids[..]) {
+            (Some("begin"), &[id]) => Entry::Begin { id, ok },
+            (Some("begin_read"), &[id]) => Entry::BeginRead { id, ok },
+            (Some("savepoint"), &[parent, id]) => Entry::Savepoint { parent, id, ok },
+            (Some("commit"), &[id]) => Entry::Commit { id, ok },
+            (Some("rollback"), &[id]) => Entry::Rollback { id, ok },
+            (Some("drop"), &[id]) if ok => Entry::Drop { id },
+            _ => return Err(invalid())
+        };
+        Ok(entry)
+    }
+}
+
+/// A line of a text log which isn't a valid [`Entry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogError {
+    line: String
+}
+
+impl Display for ParseLogError {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        write!(fter, "invalid log entry: {:?}", self.line)
+    }
+}
+
+impl Error for ParseLogError {}
+
+/// The operations recorded by a [`Recorder`], in the order they were done.
+///
+/// It's text form (see `Display` and `FromStr`) has one entry per line, e.g.
+/// `savepoint 0 1` or `commit 1 failed`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Log {
+    entries: Vec<Entry>
+}
+
+impl Log {
+
+    /// Creates a log from the given entries.
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Log { entries }
+    }
+
+    /// Returns the recorded entries.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+impl Display for Log {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                fter.write_str("\n")?;
+            }
+            Display::fmt(entry, fter)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Log {
+    type Err = ParseLogError;
+
+    fn from_str(text: &str) -> Result<Self, ParseLogError> {
+        let entries = text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Log { entries })
+    }
+}
+
+/// Serialized as the list of entries in their text form.
+#[cfg(feature = "serde")]
+impl __serde::Serialize for Log {
+    fn serialize<S: __serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entries.iter().map(|entry| entry.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> __serde::Deserialize<'de> for Log {
+    fn deserialize<D: __serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let lines = Vec::<String>::deserialize(deserializer)?;
+        let entries = lines.iter()
+            .map(|line| line.parse().map_err(<D::Error as __serde::de::Error>::custom))
+            .collect::<Result<_, _>>()?;
+        Ok(Log { entries })
+    }
+}
+
+/// The log and the id of the next transaction.
+#[derive(Default)]
+struct State {
+    log: Log,
+    next_id: usize
+}
+
+/// The state shared by a recorder and it's transactions.
+#[derive(Clone, Default)]
+struct Shared {
+    state: Arc<Mutex<State>>
+}
+
+impl Shared {
+
+    fn next_id(&self) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.next_id += 1;
+        state.next_id - 1
+    }
+
+    fn push(&self, entry: Entry) {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.log.entries.push(entry);
+    }
+
+    fn log(&self) -> Log {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).log.clone()
+    }
+}
+
+/// A connection recording all operations done through the `traits` into a [`Log`].
+pub struct Recorder<C> {
+    conn: C,
+    shared: Shared
+}
+
+impl<C> Recorder<C> {
+
+    /// Wraps the connection, starting with a empty log.
+    pub fn new(conn: C) -> Self {
+        Recorder { conn, shared: Shared::default() }
+    }
+
+    /// Returns the operations recorded so far.
+    pub fn log(&self) -> Log {
+        self.shared.log()
+    }
+
+    /// Returns a reference to the wrapped connection.
+    pub fn get(&self) -> &C {
+        &self.conn
+    }
+
+    /// Returns a mutable reference to the wrapped connection.
+    ///
+    /// Transactions begun through it directly are not recorded.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> C {
+        self.conn
+    }
+}
+
+impl<C: Connection> Connection for Recorder<C> {
+    type Error = C::Error;
+    type Transaction = Recorded<C::Transaction>;
+
+    fn begin(&mut self) -> Result<Bound<'_, Recorded<C::Transaction>>, C::Error> {
+        let (shared, id) = (self.shared.clone(), self.shared.next_id());
+        let res = self.conn.begin();
+        shared.push(Entry::Begin { id, ok: res.is_ok() });
+        res.map(|trans| Recorded::wrap(trans, shared, id))
+    }
+}
+
+impl<C: ReadConnection> ReadConnection for Recorder<C> {
+    type ReadTransaction = Recorded<C::ReadTransaction>;
+
+    fn begin_read(&mut self) -> Result<Bound<'_, Recorded<C::ReadTransaction>>, C::Error> {
+        let (shared, id) = (self.shared.clone(), self.shared.next_id());
+        let res = self.conn.begin_read();
+        shared.push(Entry::BeginRead { id, ok: res.is_ok() });
+        res.map(|trans| Recorded::wrap(trans, shared, id))
+    }
+}
+
+/// A transaction (or savepoint) begun through a [`Recorder`].
+///
+/// Use [`Recorded::get()`]/[`Recorded::get_mut()`] to access the wrapped
+/// transaction, e.g. to run queries in it.
+pub struct Recorded<T: for<'a> BoundExt<'a>> {
+    // the lifetime is erased, it actually is the one of the `Bound<Recorded<T>>`
+    inner: ManuallyDrop<Bound<'static, T>>,
+    shared: Shared,
+    id: usize
+}
+
+impl<T> Recorded<T>
+    where T: for<'a> BoundExt<'a>
+{
+    fn wrap(inner: Bound<'_, T>, shared: Shared, id: usize) -> Bound<'_, Self> {
+        let inner = unsafe_block! {
+            "only the lifetime changes, it's kept by the returned Bound" => {
+                mem::transmute::<Bound<T>, Bound<'static, T>>(inner)
+            }
+        };
+        unsafe_block! {
+            "the inner value is bound to the lifetime of the returned Bound" => {
+                Bound::new(Recorded { inner: ManuallyDrop::new(inner), shared, id })
+            }
+        }
+    }
+
+    fn unwrap(me: Bound<'_, Self>) -> (Bound<'_, T>, Shared, usize) {
+        let recorded = me._into_inner();
+        let inner = unsafe_block! {
+            "the inner value was bound to the lifetime of the consumed Bound" => {
+                mem::transmute::<Bound<'static, T>, Bound<T>>(ManuallyDrop::into_inner(recorded.inner))
+            }
+        };
+        (inner, recorded.shared, recorded.id)
+    }
+
+    /// Returns the wrapped transaction.
+    pub fn get<'a, 'b>(me: &'b Bound<'a, Self>) -> &'b Bound<'a, T> {
+        // `Bound` has a `inner` field, too
+        let recorded: &'b Recorded<T> = me;
+        unsafe_block! {
+            "the inner value is bound to 'a, which outlives 'b" => {
+                mem::transmute::<&'b Bound<'static, T>, &'b Bound<'a, T>>(&recorded.inner)
+            }
+        }
+    }
+
+    /// Returns the wrapped transaction mutably.
+    pub fn get_mut<'a, 'b>(me: &'b mut Bound<'a, Self>) -> &'b mut Bound<'a, T> {
+        unsafe_block! {
+            "the inner value is bound to 'a, which outlives 'b" => {
+                mem::transmute::<&'b mut Bound<'static, T>, &'b mut Bound<'a, T>>(
+                    &mut me._get_mut().inner)
+            }
+        }
+    }
+
+    /// Returns the id of the transaction in the log.
+    pub fn id(me: &Bound<'_, Self>) -> usize {
+        me.id
+    }
+}
+
+/// Records the drop and then drops the wrapped transaction (incl. it's `pre_drop`).
+impl<'a, T> BoundExt<'a> for Recorded<T>
+    where T: for<'x> BoundExt<'x>
+{
+    #[allow(unsafe_code)]
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        {
+            let recorded = me._get_mut();
+            recorded.shared.push(Entry::Drop { id: recorded.id });
+            ManuallyDrop::drop(mem::transmute::<
+                &mut ManuallyDrop<Bound<'static, T>>,
+                &mut ManuallyDrop<Bound<'a, T>>
+            >(&mut recorded.inner));
+        }
+        Bound::mark_pre_dropped(me);
+    }
+}
+
+impl<T> AccessMode for Recorded<T>
+    where T: for<'a> BoundExt<'a> + AccessMode
+{
+    type Access = T::Access;
+}
+
+impl<T: Transaction> Transaction for Recorded<T> {
+    type Error = T::Error;
+
+    fn commit(me: Bound<'_, Self>) -> Result<(), T::Error> {
+        let (inner, shared, id) = Recorded::unwrap(me);
+        let res = T::commit(inner);
+        shared.push(Entry::Commit { id, ok: res.is_ok() });
+        res
+    }
+
+    fn rollback(me: Bound<'_, Self>) -> Result<(), T::Error> {
+        let (inner, shared, id) = Recorded::unwrap(me);
+        let res = T::rollback(inner);
+        shared.push(Entry::Rollback { id, ok: res.is_ok() });
+        res
+    }
+}
+
+impl<T: Savepoint> Savepoint for Recorded<T> {
+    type Savepoint = Recorded<T::Savepoint>;
+
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, Recorded<T::Savepoint>>, T::Error>
+        where T::Access: Writable
+    {
+        let shared = me.shared.clone();
+        let (parent, id) = (me.id, shared.next_id());
+        let res = T::savepoint(Recorded::get_mut(me));
+        shared.push(Entry::Savepoint { parent, id, ok: res.is_ok() });
+        res.map(|savepoint| Recorded::wrap(savepoint, shared, id))
+    }
+}
+
+/// Why replaying a [`Log`] failed, `index` is the index of the entry in the log.
+#[derive(Debug)]
+pub enum ReplayError<E> {
+    /// The entry doesn't apply to the transactions open at this point of the log.
+    Unexpected { index: usize, entry: Entry },
+    /// The operation failed, but succeeded when it was recorded.
+    Failed { index: usize, error: E },
+    /// The operation succeeded, but failed when it was recorded.
+    Succeeded { index: usize }
+}
+
+impl<E: Display> Display for ReplayError<E> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Unexpected { index, entry } => write!(fter, "unexpected log entry {}: {}", index, entry),
+            ReplayError::Failed { index, ref error } => write!(fter, "log entry {} failed: {}", index, error),
+            ReplayError::Succeeded { index } => write!(fter, "log entry {} succeeded, but was recorded as failed", index)
+        }
+    }
+}
+
+impl<E: Error> Error for ReplayError<E> {}
+
+/// The (indexed) entries of a log being replayed.
+type Entries<'l> = Enumerate<Cloned<slice::Iter<'l, Entry>>>;
+
+/// Re-executes the operations in `log` on `conn`.
+///
+/// Fails on the first operation with a different outcome than recorded. Transactions
+/// still open at the end of the log are dropped. Read-only transactions are not
+/// supported (they are [`ReplayError::Unexpected`]), see [`replay_with_reads()`].
+pub fn replay<C>(log: &Log, conn: &mut C) -> Result<(), ReplayError<C::Error>>
+    where C: Connection, C::Transaction: Savepoint
+{
+    replay_with(log, conn, |_, index, entry, _| Err(ReplayError::Unexpected { index, entry }))
+}
+
+/// Like [`replay()`] but also re-executes read-only transactions.
+pub fn replay_with_reads<C>(log: &Log, conn: &mut C) -> Result<(), ReplayError<C::Error>>
+    where C: ReadConnection, C::Transaction: Savepoint
+{
+    replay_with(log, conn, |conn, index, entry, entries| match entry {
+        Entry::BeginRead { id, ok } => match check(index, ok, conn.begin_read())? {
+            Some(trans) => replay_read_scope(trans, id, entries),
+            None => Ok(())
+        },
+        entry => Err(ReplayError::Unexpected { index, entry })
+    })
+}
+
+/// Replays the transactions in `log`, `begin_read` is called for any entry not beginning
+/// a (writable) transaction.
+fn replay_with<C, F>(log: &Log, conn: &mut C, mut begin_read: F) -> Result<(), ReplayError<C::Error>>
+    where C: Connection, C::Transaction: Savepoint,
+          F: FnMut(&mut C, usize, Entry, &mut Entries) -> Result<(), ReplayError<C::Error>>
+{
+    let mut entries = log.entries.iter().cloned().enumerate();
+    while let Some((index, entry)) = entries.next() {
+        match entry {
+            Entry::Begin { id, ok } => if let Some(trans) = check(index, ok, conn.begin())? {
+                replay_scope(trans, id, &mut entries)?;
+            },
+            entry => begin_read(conn, index, entry, &mut entries)?
+        }
+    }
+    Ok(())
+}
+
+/// Replays the entries of the transaction `id` (incl. it's savepoints) until it's closed.
+fn replay_scope<T>(mut trans: Bound<'_, T>, id: usize, entries: &mut Entries) -> Result<(), ReplayError<T::Error>>
+    where T: Savepoint<Access=ReadWrite>
+{
+    while let Some((index, entry)) = entries.next() {
+        match entry {
+            Entry::Savepoint { parent, id: child, ok } if parent == id => {
+                if let Some(savepoint) = check(index, ok, Savepoint::savepoint(&mut trans))? {
+                    replay_scope(savepoint, child, entries)?;
+                }
+            },
+            Entry::Commit { id: target, ok } if target == id => {
+                return check(index, ok, Transaction::commit(trans)).map(drop);
+            },
+            Entry::Rollback { id: target, ok } if target == id => {
+                return check(index, ok, Transaction::rollback(trans)).map(drop);
+            },
+            Entry::Drop { id: target } if target == id => return Ok(()),
+            entry => return Err(ReplayError::Unexpected { index, entry })
+        }
+    }
+    Ok(())
+}
+
+/// Replays the entries of the read-only transaction `id` until it's closed.
+fn replay_read_scope<T>(trans: Bound<'_, T>, id: usize, entries: &mut Entries) -> Result<(), ReplayError<T::Error>>
+    where T: Transaction
+{
+    match entries.next() {
+        Some((index, Entry::Commit { id: target, ok })) if target == id => {
+            check(index, ok, Transaction::commit(trans)).map(drop)
+        },
+        Some((index, Entry::Rollback { id: target, ok })) if target == id => {
+            check(index, ok, Transaction::rollback(trans)).map(drop)
+        },
+        Some((_, Entry::Drop { id: target })) if target == id => Ok(()),
+        Some((index, entry)) => Err(ReplayError::Unexpected { index, entry }),
+        None => Ok(())
+    }
+}
+
+/// Compares the outcome of a replayed operation with the recorded one.
+fn check<R, E>(index: usize, ok: bool, res: Result<R, E>) -> Result<Option<R>, ReplayError<E>> {
+    match (res, ok) {
+        (Ok(value), true) => Ok(Some(value)),
+        (Err(_), false) => Ok(None),
+        (Ok(_), false) => Err(ReplayError::Succeeded { index }),
+        (Err(error), true) => Err(ReplayError::Failed { index, error })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use create_gal_wrapper_type;
+    use access::ReadOnly;
+    use super::*;
+
+    /// Fails commits if `fail_commits` is set, counts the successful ones.
+    struct Store {
+        fail_commits: bool,
+        commits: usize
+    }
+
+    struct Trans<'a> {
+        store: &'a mut Store
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+
+    struct ReadTrans<'a> {
+        _store: &'a Store
+    }
+
+    create_gal_wrapper_type!{ struct ReadTransWrap(ReadTrans<'a>); }
+
+    impl Connection for Store {
+        type Error = &'static str;
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> Result<Bound<'_, TransWrap>, &'static str> {
+            Ok(TransWrap::new(Trans { store: self }))
+        }
+    }
+
+    impl ReadConnection for Store {
+        type ReadTransaction = ReadTransWrap;
+
+        fn begin_read(&mut self) -> Result<Bound<'_, ReadTransWrap>, &'static str> {
+            Ok(ReadTransWrap::new(ReadTrans { _store: self }))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl AccessMode for ReadTransWrap {
+        type Access = ReadOnly;
+    }
+
+    impl Transaction for TransWrap {
+        type Error = &'static str;
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            let trans = TransWrap::into_inner(me);
+            if trans.store.fail_commits {
+                return Err("commit failed");
+            }
+            trans.store.commits += 1;
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    impl Transaction for ReadTransWrap {
+        type Error = &'static str;
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            drop(me);
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), &'static str> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    impl Savepoint for TransWrap {
+        type Savepoint = TransWrap;
+
+        fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, TransWrap>, &'static str> {
+            Ok(TransWrap::new(Trans { store: TransWrap::get_mut(me).store }))
+        }
+    }
+
+    fn flow<C>(conn: &mut C) -> Result<(), C::Error>
+        where C: Connection, C::Transaction: Savepoint
+    {
+        let mut trans = conn.begin()?;
+        {
+            let mut savepoint = Savepoint::savepoint(&mut trans)?;
+            drop(Savepoint::savepoint(&mut savepoint)?);
+            Transaction::commit(savepoint)?;
+        }
+        Transaction::rollback(Savepoint::savepoint(&mut trans)?)?;
+        Transaction::commit(trans)
+    }
+
+    #[test]
+    fn recorded_flows_can_be_replayed() {
+        let mut conn = Recorder::new(Store { fail_commits: false, commits: 0 });
+        flow(&mut conn).unwrap();
+        conn.get_mut().fail_commits = true;
+        assert!(flow(&mut conn).is_err());
+        assert_eq!(conn.get().commits, 2);
+
+        let log = conn.log();
+        assert_eq!(log.entries(), &[
+            // first flow, everything succeeds
+            Entry::Begin { id: 0, ok: true },
+            Entry::Savepoint { parent: 0, id: 1, ok: true },
+            Entry::Savepoint { parent: 1, id: 2, ok: true },
+            Entry::Drop { id: 2 },
+            Entry::Commit { id: 1, ok: true },
+            Entry::Savepoint { parent: 0, id: 3, ok: true },
+            Entry::Rollback { id: 3, ok: true },
+            Entry::Commit { id: 0, ok: true },
+            // second flow, committing the first savepoint fails and the transaction is dropped
+            Entry::Begin { id: 4, ok: true },
+            Entry::Savepoint { parent: 4, id: 5, ok: true },
+            Entry::Savepoint { parent: 5, id: 6, ok: true },
+            Entry::Drop { id: 6 },
+            Entry::Commit { id: 5, ok: false },
+            Entry::Drop { id: 4 }
+        ][..]);
+        assert_eq!(log.to_string().parse::<Log>(), Ok(log.clone()));
+
+        let mut store = Store { fail_commits: false, commits: 0 };
+        match replay(&log, &mut store) {
+            Err(ReplayError::Succeeded { index: 12 }) => (),
+            res => panic!("unexpected result: {:?}", res)
+        }
+        assert_eq!(store.commits, 3);
+        store.fail_commits = true;
+        match replay(&log, &mut store) {
+            Err(ReplayError::Failed { index: 4, error: "commit failed" }) => (),
+            res => panic!("unexpected result: {:?}", res)
+        }
+    }
+
+    fn read<C: ReadConnection>(conn: &mut C) -> Result<(), C::Error> {
+        Transaction::commit(conn.begin_read()?)
+    }
+
+    #[test]
+    fn read_transactions_are_recorded() {
+        let mut conn = Recorder::new(Store { fail_commits: false, commits: 0 });
+        read(&mut conn).unwrap();
+        drop(conn.begin().unwrap());
+        drop(conn.begin_read().unwrap());
+
+        let log = conn.log();
+        assert_eq!(log.to_string(), "begin_read 0\ncommit 0\nbegin 1\ndrop 1\nbegin_read 2\ndrop 2");
+        assert_eq!(log.to_string().parse::<Log>(), Ok(log.clone()));
+
+        let mut store = Store { fail_commits: false, commits: 0 };
+        replay_with_reads(&log, &mut store).unwrap();
+        match replay(&log, &mut store) {
+            Err(ReplayError::Unexpected { index: 0, entry: Entry::BeginRead { id: 0, ok: true } }) => (),
+            res => panic!("unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn invalid_logs_are_rejected() {
+        assert!("begin 0\nsavepoint 0".parse::<Log>().is_err());
+        assert!("drop 1 failed".parse::<Log>().is_err());
+        let log = "begin 0\ncommit 1".parse::<Log>().unwrap();
+        let mut store = Store { fail_commits: false, commits: 0 };
+        match replay(&log, &mut store) {
+            Err(ReplayError::Unexpected { index: 1, entry: Entry::Commit { id: 1, ok: true } }) => (),
+            res => panic!("unexpected result: {:?}", res)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn logs_can_be_serialized() {
+        extern crate serde_json;
+
+        let log = "begin 0\nrollback 0 failed".parse::<Log>().unwrap();
+        let json = serde_json::to_string(&log).unwrap();
+        assert_eq!(json, r#"["begin 0","rollback 0 failed"]"#);
+        assert_eq!(serde_json::from_str::<Log>(&json).unwrap(), log);
+    }
+}
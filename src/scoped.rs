@@ -0,0 +1,182 @@
+//! Running work on bound values in scoped threads.
+//!
+//! `Bound<'a, T>` is `Send`/`Sync` if `T` is, and the wrappers created by
+//! [`create_gal_wrapper_type`](crate::create_gal_wrapper_type) are `Send`/`Sync`
+//! if the wrapped type is (or if they opted in with `unsafe impl Send;`). As the
+//! bound value borrows from it's owner it can't be moved into a normal thread, but
+//! it can be moved into a scoped thread, as the scope joins all threads before the
+//! borrow ends. [`spawn()`] and [`run()`] do so and make the lifetime requirements
+//! explicit:
+//!
+//! ```
+//! # #[macro_use] extern crate galemu;
+//! use galemu::scoped;
+//!
+//! struct Connection { log: Vec<&'static str> }
+//! struct Transaction<'conn> { conn: &'conn mut Connection }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+//!
+//! # fn main() {
+//! let mut conn = Connection { log: Vec::new() };
+//! let trans = TransWrap::new(Transaction { conn: &mut conn });
+//! let count = scoped::run(trans, |trans| {
+//!     let trans = TransWrap::into_inner(trans);
+//!     trans.conn.log.push("blocking work");
+//!     trans.conn.log.len()
+//! });
+//! assert_eq!(count, 1);
+//! assert_eq!(conn.log, &["blocking work"]);
+//! # }
+//! ```
+//!
+//! Wrapped types which are not `Send` can't be moved to another thread:
+//!
+//! ```compile_fail
+//! # #[macro_use] extern crate galemu;
+//! use std::rc::Rc;
+//! use galemu::scoped;
+//!
+//! struct Transaction<'conn> { conn: &'conn Rc<u32> }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+//!
+//! # fn main() {
+//! let conn = Rc::new(0);
+//! // error: `Rc<u32>` cannot be shared between threads safely
+//! scoped::run(TransWrap::new(Transaction { conn: &conn }), drop);
+//! # }
+//! ```
+//!
+//! And the owner stays borrowed while the thread runs:
+//!
+//! ```compile_fail
+//! # #[macro_use] extern crate galemu;
+//! use std::thread;
+//! use galemu::scoped;
+//!
+//! struct Transaction<'conn> { conn: &'conn mut u32 }
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+//!
+//! # fn main() {
+//! let mut conn = 0;
+//! thread::scope(|scope| {
+//!     let handle = scoped::spawn(scope, TransWrap::new(Transaction { conn: &mut conn }), drop);
+//!     // error: cannot use `conn` because it was mutably borrowed
+//!     conn += 1;
+//!     handle.join().unwrap();
+//! });
+//! # }
+//! ```
+//!
+//! This module requires the `scoped` feature and Rust 1.63.
+use std::{
+    panic,
+    thread::{self, Scope, ScopedJoinHandle}
+};
+
+use {Bound, BoundExt};
+
+/// Spawns a scoped thread running `work` with the bound value.
+///
+/// The bound value (and the owner it borrows from) has to outlive the scope.
+pub fn spawn<'scope, 'env, 'a, T, F, R>(
+    scope: &'scope Scope<'scope, 'env>,
+    bound: Bound<'a, T>,
+    work: F
+) -> ScopedJoinHandle<'scope, R>
+    where 'a: 'scope,
+          T: BoundExt<'a> + Send,
+          F: FnOnce(Bound<'a, T>) -> R + Send + 'scope,
+          R: Send + 'scope
+{
+    scope.spawn(move || work(bound))
+}
+
+/// Runs `work` with the bound value on a new thread and waits for it to finish.
+///
+/// If `work` panics the panic is resumed on the calling thread.
+pub fn run<'a, T, F, R>(bound: Bound<'a, T>, work: F) -> R
+    where T: BoundExt<'a> + Send,
+          F: FnOnce(Bound<'a, T>) -> R + Send,
+          R: Send
+{
+    thread::scope(|scope| {
+        spawn(scope, bound, work)
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{marker::PhantomData, sync::Mutex};
+    use create_gal_wrapper_type;
+    use super::*;
+
+    struct Worker<'conn> {
+        queue: &'conn Mutex<Vec<u32>>
+    }
+
+    create_gal_wrapper_type!{
+        struct WorkerWrap(Worker<'a>);
+        on_drop = |worker| worker.queue.lock().unwrap().push(0);
+    }
+
+    /// A raw pointer which is only ever used by one thread at a time.
+    struct Cursor<'buf> {
+        pos: *mut u32,
+        _buf: PhantomData<&'buf mut [u32]>
+    }
+
+    impl<'buf> Cursor<'buf> {
+        fn new(buf: &'buf mut [u32]) -> Self {
+            Cursor { pos: buf.as_mut_ptr(), _buf: PhantomData }
+        }
+    }
+
+    create_gal_wrapper_type!{
+        struct CursorWrap(Cursor<'a>);
+        unsafe impl Send;
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn bound_values_can_be_used_by_scoped_threads() {
+        let queue = Mutex::new(Vec::new());
+        let (first, second) = thread::scope(|scope| {
+            let first = spawn(scope, WorkerWrap::new(Worker { queue: &queue }), |worker| {
+                WorkerWrap::get(&worker).queue.lock().unwrap().push(1);
+                1
+            });
+            let second = spawn(scope, WorkerWrap::new(Worker { queue: &queue }), |_| 2);
+            (first.join().unwrap(), second.join().unwrap())
+        });
+        assert_eq!((first, second), (1, 2));
+        let mut pushed = queue.into_inner().unwrap();
+        pushed.sort();
+        assert_eq!(pushed, &[0, 0, 1]);
+
+        let mut buf = [0, 0];
+        let cursor = CursorWrap::new(Cursor::new(&mut buf));
+        assert_send(&cursor);
+        run(cursor, |cursor| {
+            let cursor = CursorWrap::into_inner(cursor);
+            unsafe_block!{ "the pointer points into the borrowed buffer" => { *cursor.pos = 4 } }
+        });
+        assert_eq!(buf, [4, 0]);
+    }
+
+    #[test]
+    fn panics_are_resumed_after_pre_drop() {
+        let queue = Mutex::new(Vec::new());
+        let result = panic::catch_unwind(|| {
+            run(WorkerWrap::new(Worker { queue: &queue }), |_worker| panic!("failed"))
+        });
+        assert_eq!(result.unwrap_err().downcast_ref::<&str>(), Some(&"failed"));
+        assert_eq!(queue.into_inner().unwrap(), &[0]);
+    }
+}
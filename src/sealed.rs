@@ -0,0 +1,34 @@
+//! Supertraits sealing the versioned extension traits (`Connection2` etc.).
+//!
+//! The extension traits are implemented for every implementor of their base trait,
+//! so methods can be added to them without breaking other crates. Requiring these
+//! (unnameable) traits keeps other crates from implementing (or overriding the
+//! methods of) the extension traits themselves.
+
+pub trait Connection {}
+
+impl<C: ?Sized + ::traits::Connection> Connection for C {}
+
+pub trait ReadConnection {}
+
+impl<C: ?Sized + ::traits::ReadConnection> ReadConnection for C {}
+
+pub trait Transaction {}
+
+impl<T: ::traits::Transaction> Transaction for T {}
+
+pub trait GeneralCursor {}
+
+impl<C: ?Sized + ::cursor::GeneralCursor> GeneralCursor for C {}
+
+#[cfg(feature = "async_traits")]
+pub trait AsyncConnection {}
+
+#[cfg(feature = "async_traits")]
+impl<C: ?Sized + ::async_traits::AsyncConnection> AsyncConnection for C {}
+
+#[cfg(feature = "async_traits")]
+pub trait AsyncTransaction {}
+
+#[cfg(feature = "async_traits")]
+impl<T: ::async_traits::AsyncTransaction> AsyncTransaction for T {}
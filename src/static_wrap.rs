@@ -0,0 +1,127 @@
+use std::ops::Deref;
+
+use {Bound, BoundExt, Rebind};
+
+/// Wrapper for values which don't borrow anything, so they can be bound to any lifetime.
+///
+/// Some backends have owned transaction types (without a lifetime) while
+/// others borrow the connection. Wrapping the owned type in `Static` allows
+/// both to implement the same trait (e.g. one using [`Rebind`] or
+/// [`traits::Transaction`](crate::traits::Transaction)).
+///
+/// ```
+/// use galemu::{Bound, Static};
+///
+/// struct OwnedTransaction { id: u32 }
+///
+/// struct Connection;
+///
+/// impl Connection {
+///     fn begin(&mut self) -> Bound<'_, Static<OwnedTransaction>> {
+///         Bound::new_static(OwnedTransaction { id: 12 })
+///     }
+/// }
+///
+/// let mut conn = Connection;
+/// let trans = conn.begin();
+/// assert_eq!(trans.id, 12);
+/// ```
+///
+/// Note that `Bound<'a, Static<T>>` can't be turned into `Bound<'a, T>`,
+/// wrapping a value of a type created with [`create_gal_wrapper_type`](crate::create_gal_wrapper_type)
+/// in `Static` is safe but won't give access to the correctly lifetimed inner
+/// value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Static<T: 'static>(pub T);
+
+impl<T: 'static> Static<T> {
+
+    /// Returns a reference to the wrapped value.
+    pub fn get<'b>(me: &'b Bound<'_, Self>) -> &'b T {
+        &me.0
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    ///
+    /// Unlike for other wrappers this is safe, as the value isn't bound
+    /// to the lifetime in any way.
+    pub fn get_mut<'b>(me: &'b mut Bound<'_, Self>) -> &'b mut T {
+        let me = unsafe_block! {
+            "T is 'static so swapping it with another bound value is fine" => {
+                me._get_mut()
+            }
+        };
+        &mut me.0
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(me: Bound<'_, Self>) -> T {
+        me._into_inner().0
+    }
+}
+
+impl<T: 'static> Deref for Static<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'a, T: 'static> BoundExt<'a> for Static<T> {}
+
+impl<'a, T: 'static> Rebind<'a> for Static<T> {
+    type Inner = T;
+
+    fn unbind(me: Bound<'a, Self>) -> T {
+        Static::into_inner(me)
+    }
+
+    fn bind(inner: T) -> Bound<'a, Self> {
+        Bound::new_static(inner)
+    }
+}
+
+impl<'a, T: 'static> Bound<'a, Static<T>> {
+
+    /// Binds a value which doesn't borrow anything to the lifetime `'a`.
+    pub fn new_static(value: T) -> Self {
+        unsafe_block! {
+            "T is 'static so it's valid for any lifetime" => {
+                Bound::new(Static(value))
+            }
+        }
+    }
+}
+
+impl<'a, T: 'static> From<T> for Bound<'a, Static<T>> {
+    fn from(value: T) -> Self {
+        Bound::new_static(value)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use {Bound, Rebind};
+    use super::*;
+
+    fn rebind_generic<'a, 'b, T>(bound: Bound<'a, T>) -> Bound<'b, T>
+        where T: for<'x> Rebind<'x, Inner=String>
+    {
+        Bound::rebind(bound, |mut value| {
+            value.push('!');
+            value
+        })
+    }
+
+    #[test]
+    fn static_values_can_be_bound_to_any_lifetime() {
+        let mut bound: Bound<Static<String>> = Bound::from("hy".to_owned());
+        Static::get_mut(&mut bound).push_str(" there");
+        let bound = rebind_generic(bound);
+        assert_eq!(Static::get(&bound), "hy there!");
+        assert_eq!(bound.len(), 9);
+        assert_eq!(Static::into_inner(bound), "hy there!");
+    }
+}
@@ -0,0 +1,201 @@
+//! Ready made `Bound` wrappers for the guard types of the standard library.
+//!
+//! This allows abstracting over "lockable storage" using associated types, e.g.:
+//!
+//! ```
+//! use std::{cell::RefCell, sync::Mutex};
+//! use galemu::{Bound, Rebind, std_wrappers::{MutexGuardWrap, RefMutWrap}};
+//!
+//! trait Storage {
+//!     type Guard: for<'a> Rebind<'a>;
+//!
+//!     fn lock(&self) -> Bound<Self::Guard>;
+//! }
+//!
+//! impl Storage for Mutex<u32> {
+//!     type Guard = MutexGuardWrap<u32>;
+//!
+//!     fn lock(&self) -> Bound<Self::Guard> {
+//!         MutexGuardWrap::new(Mutex::lock(self).unwrap())
+//!     }
+//! }
+//!
+//! impl Storage for RefCell<u32> {
+//!     type Guard = RefMutWrap<u32>;
+//!
+//!     fn lock(&self) -> Bound<Self::Guard> {
+//!         RefMutWrap::new(self.borrow_mut())
+//!     }
+//! }
+//! ```
+//!
+//! All wrappers provide `new`, `get`, `get_mut` and `into_inner` functions like the
+//! types created by `create_gal_wrapper_type` and implement [`Rebind`].
+//!
+//! The guarded types must be `'static` as the guard's lifetime is erased to `'static`.
+use std::{
+    cell::{Ref, RefMut},
+    mem::{self, ManuallyDrop},
+    sync::{MutexGuard, RwLockReadGuard, RwLockWriteGuard}
+};
+
+use {Bound, BoundExt, Rebind};
+
+macro_rules! guard_wrapper {
+    ($(#[$attr:meta])* pub struct $Wrap:ident($Guard:ident);) => (
+
+        $(#[$attr])*
+        pub struct $Wrap<T: ?Sized + 'static> {
+            guard: ManuallyDrop<$Guard<'static, T>>
+        }
+
+        impl<T: ?Sized + 'static> $Wrap<T> {
+
+            /// Wraps the guard lifting it's lifetime to `Bound`.
+            pub fn new<'a>(guard: $Guard<'a, T>) -> Bound<'a, Self> {
+                let guard = unsafe_block! {
+                    "same type except the lifetime, which is kept by Bound" => {
+                        mem::transmute::<$Guard<'a, T>, $Guard<'static, T>>(guard)
+                    }
+                };
+                unsafe_block! {
+                    "the guard was created with the lifetime 'a" => {
+                        Bound::new($Wrap { guard: ManuallyDrop::new(guard) })
+                    }
+                }
+            }
+
+            /// Returns a reference to the guard with it's original lifetime.
+            pub fn get<'a: 'b, 'b>(me: &'b Bound<'a, Self>) -> &'b $Guard<'a, T> {
+                let ptr: *const $Guard<'static, T> = &*me.guard;
+                unsafe_block! {
+                    "the guard was created with the lifetime 'a due to Bound's guarantees" => {
+                        &*(ptr as *const $Guard<'a, T>)
+                    }
+                }
+            }
+
+            /// Returns a mutable reference to the guard with it's original lifetime.
+            pub fn get_mut<'a: 'b, 'b>(me: &'b mut Bound<'a, Self>) -> &'b mut $Guard<'a, T> {
+                let ptr: *mut $Guard<'static, T> = unsafe_block! {
+                    "the guard is only reachable with it's original lifetime" => {
+                        &mut *me._get_mut().guard
+                    }
+                };
+                unsafe_block! {
+                    "the guard was created with the lifetime 'a due to Bound's guarantees" => {
+                        &mut *(ptr as *mut $Guard<'a, T>)
+                    }
+                }
+            }
+
+            /// Returns the guard with it's original lifetime.
+            pub fn into_inner<'a>(me: Bound<'a, Self>) -> $Guard<'a, T> {
+                let guard = ManuallyDrop::into_inner(me._into_inner().guard);
+                unsafe_block! {
+                    "the guard was created with the lifetime 'a due to Bound's guarantees" => {
+                        mem::transmute::<$Guard<'static, T>, $Guard<'a, T>>(guard)
+                    }
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'static> BoundExt<'a> for $Wrap<T> {
+
+            #[allow(unsafe_code)]
+            unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+                // Safe due to the constraints of only calling drop after pre_drop
+                let ptr: *mut ManuallyDrop<$Guard<'static, T>> = &mut me._get_mut().guard;
+                ManuallyDrop::drop(&mut *(ptr as *mut ManuallyDrop<$Guard<'a, T>>));
+                Bound::mark_pre_dropped(me);
+            }
+        }
+
+        impl<'a, T: ?Sized + 'static> Rebind<'a> for $Wrap<T> {
+            type Inner = $Guard<'a, T>;
+
+            fn unbind(me: Bound<'a, Self>) -> $Guard<'a, T> {
+                Self::into_inner(me)
+            }
+
+            fn bind(inner: $Guard<'a, T>) -> Bound<'a, Self> {
+                Self::new(inner)
+            }
+        }
+    );
+}
+
+guard_wrapper! {
+    /// Wraps a `std::sync::MutexGuard` erasing it's lifetime.
+    pub struct MutexGuardWrap(MutexGuard);
+}
+
+guard_wrapper! {
+    /// Wraps a `std::sync::RwLockReadGuard` erasing it's lifetime.
+    pub struct RwLockReadGuardWrap(RwLockReadGuard);
+}
+
+guard_wrapper! {
+    /// Wraps a `std::sync::RwLockWriteGuard` erasing it's lifetime.
+    pub struct RwLockWriteGuardWrap(RwLockWriteGuard);
+}
+
+guard_wrapper! {
+    /// Wraps a `std::cell::Ref` erasing it's lifetime.
+    pub struct RefWrap(Ref);
+}
+
+guard_wrapper! {
+    /// Wraps a `std::cell::RefMut` erasing it's lifetime.
+    pub struct RefMutWrap(RefMut);
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::RefCell,
+        sync::{Mutex, RwLock}
+    };
+    use super::*;
+
+    #[test]
+    fn mutex_guards_can_be_wrapped() {
+        let mutex = Mutex::new(vec![1u8]);
+        {
+            let mut guard = MutexGuardWrap::new(mutex.lock().unwrap());
+            MutexGuardWrap::get_mut(&mut guard).push(2);
+            assert_eq!(**MutexGuardWrap::get(&guard), &[1, 2]);
+            assert!(mutex.try_lock().is_err());
+        }
+        assert_eq!(*mutex.try_lock().unwrap(), &[1, 2]);
+    }
+
+    #[test]
+    fn rw_lock_guards_can_be_wrapped() {
+        let lock = RwLock::new(1u32);
+        {
+            let guard = RwLockWriteGuardWrap::new(lock.write().unwrap());
+            let mut guard = RwLockWriteGuardWrap::into_inner(guard);
+            *guard += 1;
+        }
+        let guard1 = RwLockReadGuardWrap::new(lock.read().unwrap());
+        let guard2 = RwLockReadGuardWrap::new(lock.read().unwrap());
+        assert_eq!(**RwLockReadGuardWrap::get(&guard1), 2);
+        assert_eq!(**RwLockReadGuardWrap::get(&guard2), 2);
+        assert!(lock.try_write().is_err());
+    }
+
+    #[test]
+    fn cell_refs_can_be_wrapped() {
+        let cell = RefCell::new(String::from("a"));
+        {
+            let mut guard = RefMutWrap::new(cell.borrow_mut());
+            RefMutWrap::get_mut(&mut guard).push('b');
+            assert!(cell.try_borrow().is_err());
+        }
+        let guard = RefWrap::new(cell.borrow());
+        assert_eq!(&**RefWrap::get(&guard), "ab");
+        assert!(cell.try_borrow_mut().is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use {Bound, BoundExt, pre_drop_part};
+
+/// A bound value which can be taken out while keeping the `Bound` alive.
+///
+/// Created with [`Bound::takeable()`], the value can be moved out with [`Bound::take()`]
+/// leaving a tombstone behind. Afterwards all access returns `None` and dropping the
+/// `Bound` does nothing (no `pre_drop` is run for a taken value). This allows e.g.
+/// committing a transaction early while keeping the handle around.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::Bound;
+///
+/// struct Transaction<'conn> { log: &'conn mut Vec<&'static str> }
+///
+/// impl<'conn> Transaction<'conn> {
+///     fn commit(self) { self.log.push("commit") }
+///     fn rollback(self) { self.log.push("rollback") }
+/// }
+///
+/// create_gal_wrapper_type! {
+///     struct TransWrap(Transaction<'a>);
+///     on_drop = |trans| trans.rollback();
+/// }
+///
+/// # fn main() {
+/// let mut log = Vec::new();
+/// {
+///     let mut trans = Bound::takeable(TransWrap::new(Transaction { log: &mut log }));
+///     TransWrap::into_inner(Bound::take(&mut trans).unwrap()).commit();
+///     // the handle is still around, but the transaction is gone
+///     assert!(trans.is_taken());
+/// }
+/// assert_eq!(log, &["commit"]);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Takeable<T> {
+    inner: Option<T>
+}
+
+impl<T> Takeable<T> {
+
+    /// Returns true if the value was taken.
+    pub fn is_taken(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    /// Returns a reference to the (lifetime erased) value if it wasn't taken.
+    ///
+    /// This is the same as the `Deref` impl of `Bound<'a, T>` would give.
+    pub fn get(&self) -> Option<&T> {
+        self.inner.as_ref()
+    }
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+    /// Makes the bound value takeable, see [`Takeable`].
+    pub fn takeable(me: Self) -> Bound<'a, Takeable<T>> {
+        let inner = Takeable { inner: Some(me._into_inner()) };
+        unsafe_block! {
+            "the value is still bound to 'a" => {
+                Bound::new(inner)
+            }
+        }
+    }
+}
+
+impl<'a, T> Bound<'a, Takeable<T>>
+    where T: BoundExt<'a>
+{
+    /// Moves the value out, returns `None` if it already was taken.
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::take(bound)`.
+    pub fn take(me: &mut Self) -> Option<Bound<'a, T>> {
+        let inner = unsafe_block! {
+            "only a `None` is put in, which isn't bound to anything" => {
+                me._get_mut().inner.take()
+            }
+        };
+        inner.map(|inner| unsafe_block! {
+            "the value was bound to 'a" => {
+                Bound::new(inner)
+            }
+        })
+    }
+
+    /// Calls `func` with the value if it wasn't taken.
+    ///
+    /// The value is temporary taken out for this, i.e. if `func` panics it is
+    /// dropped (incl. `pre_drop`) while unwinding and then counts as taken.
+    pub fn with_untaken<R, F>(me: &mut Self, func: F) -> Option<R>
+        where F: FnOnce(&mut Bound<'a, T>) -> R
+    {
+        let mut inner = Bound::take(me)?;
+        let result = func(&mut inner);
+        unsafe_block! {
+            "the value is bound to 'a" => {
+                me._get_mut().inner = Some(inner._into_inner());
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Runs `pre_drop` of the value if it wasn't taken.
+impl<'a, T> BoundExt<'a> for Takeable<T>
+    where T: BoundExt<'a>
+{
+    #[allow(unsafe_code)]
+    unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+        if let Some(inner) = me._get_mut().inner.as_mut() {
+            pre_drop_part(inner);
+        }
+        Bound::mark_pre_dropped(me);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use testing::{DropEvent, DropLog, DropProbeWrap};
+    use super::*;
+
+    #[test]
+    fn taken_values_are_not_pre_dropped() {
+        let log = DropLog::new();
+        {
+            let mut kept = Bound::takeable(DropProbeWrap::new(log.probe("kept")));
+            let name = Bound::with_untaken(&mut kept, |probe| DropProbeWrap::get(probe).name());
+            assert_eq!(name, Some("kept"));
+            assert!(!kept.is_taken());
+
+            let mut taken = Bound::takeable(DropProbeWrap::new(log.probe("taken")));
+            drop(DropProbeWrap::into_inner(Bound::take(&mut taken).unwrap()));
+            assert!(taken.is_taken());
+            assert!(taken.get().is_none());
+            assert!(Bound::take(&mut taken).is_none());
+            assert!(Bound::with_untaken(&mut taken, |_| ()).is_none());
+        }
+        assert_eq!(log.events(), &[
+            DropEvent::Drop("taken"),
+            DropEvent::PreDrop("kept"),
+            DropEvent::Drop("kept")
+        ]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Helpers for testing the soundness of hand-written `Bound` wrappers.
+//!
+//! Wrappers not created by [`create_gal_wrapper_type`](crate::create_gal_wrapper_type)
+//! contain unsafe code which has to make sure that the erased lifetime can't escape.
+//! The `assert_*` functions only type-check if the given values fulfill some lifetime
+//! constraint, which makes them useful in `compile_fail` tests:
+//!
+//! ```compile_fail
+//! use galemu::{create_gal_wrapper_type, testing::assert_static};
+//!
+//! struct Transaction<'conn>(&'conn mut u32);
+//!
+//! create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+//!
+//! let mut conn = 0;
+//! let trans = TransWrap::new(Transaction(&mut conn));
+//! // error: `conn` does not live long enough
+//! assert_static(TransWrap::get(&trans));
+//! ```
+//!
+//! [`DropLog`] and [`DropProbe`] record the order in which values are pre-dropped and
+//! dropped, e.g. to check that `pre_drop` runs before the inner value is dropped and
+//! that `into_inner` doesn't run it:
+//!
+//! ```
+//! use galemu::testing::{DropEvent, DropLog, DropProbeWrap};
+//!
+//! let log = DropLog::new();
+//! drop(DropProbeWrap::new(log.probe("dropped")));
+//! drop(DropProbeWrap::into_inner(DropProbeWrap::new(log.probe("unwrapped"))));
+//! assert_eq!(log.events(), &[
+//!     DropEvent::PreDrop("dropped"),
+//!     DropEvent::Drop("dropped"),
+//!     DropEvent::Drop("unwrapped")
+//! ]);
+//! ```
+use std::cell::RefCell;
+
+use {Bound, BoundExt};
+use create_gal_wrapper_type;
+
+/// Only type-checks if `value` is `'static`.
+///
+/// Values borrowing from a bound value (e.g. returned by `get`) must fail this.
+pub fn assert_static<T: ?Sized + 'static>(_value: &T) {}
+
+/// Only type-checks if the bound value can be bound to the lifetime of `source`.
+///
+/// As `Bound` is invariant this ties the two lifetimes together, i.e. using
+/// `source` in a conflicting way while `bound` is alive must fail to compile.
+pub fn assert_bound_to<'a, T, S>(_bound: &Bound<'a, T>, _source: &'a S)
+    where T: BoundExt<'a>, S: ?Sized
+{}
+
+/// A event recorded by a [`DropLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropEvent {
+    /// The pre-drop logic of the named value ran.
+    PreDrop(&'static str),
+    /// The named value was dropped.
+    Drop(&'static str)
+}
+
+/// Records [`DropEvent`]s in the order in which they happen.
+#[derive(Debug, Default)]
+pub struct DropLog {
+    events: RefCell<Vec<DropEvent>>
+}
+
+impl DropLog {
+
+    /// Creates a new empty log.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records an event.
+    pub fn record(&self, event: DropEvent) {
+        self.events.borrow_mut().push(event)
+    }
+
+    /// Creates a probe recording it's events into this log.
+    pub fn probe(&self, name: &'static str) -> DropProbe<'_> {
+        DropProbe { name, log: self }
+    }
+
+    /// Returns all events recorded so far.
+    pub fn events(&self) -> Vec<DropEvent> {
+        self.events.borrow().clone()
+    }
+}
+
+/// Records [`DropEvent::Drop`] when dropped.
+///
+/// Place it into the value wrapped by a hand-written wrapper and call
+/// [`DropProbe::pre_drop()`] from it's [`BoundExt::pre_drop()`] implementation.
+/// [`DropProbeWrap`] is a ready made wrapper doing so.
+#[derive(Debug)]
+pub struct DropProbe<'log> {
+    name: &'static str,
+    log: &'log DropLog
+}
+
+impl<'log> DropProbe<'log> {
+
+    /// The name used for the recorded events.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Records [`DropEvent::PreDrop`].
+    pub fn pre_drop(&self) {
+        self.log.record(DropEvent::PreDrop(self.name))
+    }
+}
+
+impl<'log> Drop for DropProbe<'log> {
+    fn drop(&mut self) {
+        self.log.record(DropEvent::Drop(self.name))
+    }
+}
+
+create_gal_wrapper_type! {
+    /// Wraps a [`DropProbe`] calling [`DropProbe::pre_drop()`] before dropping it.
+    pub struct DropProbeWrap(DropProbe<'a>);
+    on_drop = |probe| probe.pre_drop();
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn probes_record_the_drop_order() {
+        let log = DropLog::new();
+        {
+            let first = DropProbeWrap::new(log.probe("first"));
+            assert_eq!(DropProbeWrap::get(&first).name(), "first");
+            let both = Bound::zip(first, DropProbeWrap::new(log.probe("second")));
+            assert_bound_to(&both, &log);
+        }
+        assert_eq!(log.events(), &[
+            DropEvent::PreDrop("first"),
+            DropEvent::Drop("first"),
+            DropEvent::PreDrop("second"),
+            DropEvent::Drop("second")
+        ]);
+    }
+}
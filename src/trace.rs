@@ -0,0 +1,124 @@
+//! `tracing` instrumentation, see the "Tracing" section of the `Bound` docs.
+use std::any::type_name;
+
+/// Emits a event for a `Bound<'a, T>`.
+pub fn bound_event<T>(event: &'static str) {
+    trace!(target: "galemu::bound", event, ty = type_name::<T>());
+}
+
+/// Emits a event for the wrapper with the given name.
+pub fn wrapper_event(event: &'static str, wrapper: &'static str) {
+    trace!(target: "galemu::wrapper", event, wrapper);
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fmt,
+        sync::{Arc, Mutex}
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span, subscriber, Event, Metadata, Subscriber
+    };
+    use {Bound, create_gal_wrapper_type};
+
+    /// Records `(target, event, type or wrapper name)` of all events.
+    #[derive(Default)]
+    struct Recorder {
+        events: Arc<Mutex<Vec<(String, String, String)>>>
+    }
+
+    #[derive(Default)]
+    struct Fields {
+        event: String,
+        name: String
+    }
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            match field.name() {
+                "event" => self.event = value.to_owned(),
+                _ => self.name = value.to_owned()
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &span::Attributes) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let target = event.metadata().target().to_owned();
+            self.events.lock().unwrap().push((target, fields.event, fields.name));
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    struct Statement<'conn>(&'conn u32);
+
+    create_gal_wrapper_type!{ struct StatementWrap(Statement<'a>); }
+
+    #[test]
+    fn lifecycle_events_are_emitted() {
+        let recorder = Recorder::default();
+        let events = recorder.events.clone();
+        subscriber::with_default(recorder, || {
+            let conn = 1;
+            drop(StatementWrap::new(Statement(&conn)));
+            let stmt = StatementWrap::into_inner(StatementWrap::new(Statement(&conn)));
+            assert_eq!(*stmt.0, 1);
+        });
+
+        let events = events.lock().unwrap();
+        let wrapper_events = events.iter()
+            .filter(|&(target, _, name)| target == "galemu::wrapper" && name == "StatementWrap")
+            .map(|(_, event, _)| &event[..])
+            .collect::<Vec<_>>();
+        assert_eq!(wrapper_events, &["new", "pre_drop", "new", "into_inner"]);
+        let bound_events = events.iter()
+            .filter(|&(target, _, name)| target == "galemu::bound" && name.ends_with("StatementWrap"))
+            .map(|(_, event, _)| &event[..])
+            .collect::<Vec<_>>();
+        assert_eq!(bound_events, &["new", "drop", "new", "into_inner"]);
+    }
+
+    #[test]
+    fn dropping_zipped_values_emits_no_internal_events() {
+        let recorder = Recorder::default();
+        let events = recorder.events.clone();
+        subscriber::with_default(recorder, || {
+            let conn = 1;
+            let zipped = Bound::zip(StatementWrap::new(Statement(&conn)), StatementWrap::new(Statement(&conn)));
+            events.lock().unwrap().clear();
+            drop(zipped);
+        });
+
+        let events = events.lock().unwrap();
+        let events = events.iter()
+            .map(|(target, event, _)| (&target[..], &event[..]))
+            .collect::<Vec<_>>();
+        assert_eq!(events, &[
+            ("galemu::bound", "drop"),
+            ("galemu::wrapper", "pre_drop"),
+            ("galemu::wrapper", "pre_drop")
+        ]);
+    }
+}
@@ -0,0 +1,581 @@
+//! Standard connection/transaction traits built on [`Bound`].
+//!
+//! This are the `GeneralConnection`/`GeneralTransaction` traits from the
+//! crate level documentation with results and savepoints added. Sharing them
+//! allows code generic over "a transactional store" to work with any backend
+//! which implements them, including the ones in the [`integrations`](crate::integrations)
+//! module.
+//!
+//! ```
+//! use galemu::{Bound, traits::{Connection, Transaction, Savepoint}};
+//!
+//! fn run_twice<C>(conn: &mut C) -> Result<(), C::Error>
+//!     where C: Connection, C::Transaction: Savepoint
+//! {
+//!     let mut trans = conn.begin()?;
+//!     for _ in 0..2 {
+//!         let savepoint = Savepoint::savepoint(&mut trans)?;
+//!         // ... use the savepoint ...
+//!         Transaction::commit(savepoint)?;
+//!     }
+//!     Transaction::commit(trans)
+//! }
+//! ```
+//!
+//! Every transaction declares it's [access mode](crate::access). [`Connection::begin`]
+//! returns writable transactions, [`ReadConnection::begin_read`] read-only ones and
+//! creating savepoints requires a writable transaction. So generic code can require
+//! `T::Access: Writable` for write operations, which then can't be used with a
+//! read-only transaction:
+//!
+//! ```compile_fail
+//! use galemu::{Bound, access::Writable, traits::{ReadConnection, Transaction}};
+//!
+//! fn write<T>(_trans: &mut Bound<'_, T>)
+//!     where T: Transaction, T::Access: Writable
+//! {}
+//!
+//! fn read_only<C: ReadConnection>(conn: &mut C) -> Result<(), C::Error> {
+//!     let mut trans = conn.begin_read()?;
+//!     // error: `ReadOnly` is not `Writable`
+//!     write(&mut trans);
+//!     Transaction::commit(trans)
+//! }
+//! ```
+//!
+//! # Extending the traits
+//!
+//! The traits are implemented by other crates, so adding required methods (or
+//! associated types) to them would be a breaking change. They are extended in two
+//! ways instead:
+//!
+//! - Methods which can be implemented on top of the existing ones go into a versioned
+//!   extension trait, e.g. [`Connection2`] for [`Connection`]. These are implemented
+//!   for all implementors of the base trait and can't be implemented (or overridden)
+//!   by other crates, so new methods can be added to them at any time. If they ever
+//!   need a breaking change a `Connection3` is added next to `Connection2`.
+//! - Methods a backend has to be able to override are added to the base trait as
+//!   provided methods with a default implementation.
+//!
+//! Savepoints are extended through functions on `Bound<'_, T>` with `T: Savepoint`
+//! like [`Bound::speculate()`], which can't be added by other crates either.
+use {Bound, BoundExt};
+use access::{AccessMode, ReadOnly, ReadWrite, Writable};
+use sealed;
+
+/// A connection (or similar) which can begin transactions.
+pub trait Connection {
+    /// The error returned by the connection and it's transactions.
+    type Error;
+    /// The (lifetime erased) transaction type.
+    ///
+    /// The `'static` bound is implied by `Transaction: for<'a> BoundExt<'a>`, but
+    /// has to be explicit for the `&mut C`/`Box<C>` impls to type-check.
+    type Transaction: Transaction<Error=Self::Error, Access=ReadWrite> + 'static;
+
+    /// Begins a new (writable) transaction bound to the connection.
+    fn begin(&mut self) -> Result<Bound<'_, Self::Transaction>, Self::Error>;
+}
+
+/// A connection which can also begin read-only transactions.
+pub trait ReadConnection: Connection {
+    /// The (lifetime erased) read-only transaction type.
+    type ReadTransaction: Transaction<Error=Self::Error, Access=ReadOnly> + 'static;
+
+    /// Begins a new read-only transaction bound to the connection.
+    fn begin_read(&mut self) -> Result<Bound<'_, Self::ReadTransaction>, Self::Error>;
+}
+
+/// A transaction which can be committed or rolled back.
+///
+/// Dropping a transaction without committing it should roll it back.
+pub trait Transaction: for<'a> BoundExt<'a> + AccessMode {
+    /// The error returned by the transaction.
+    type Error;
+
+    /// Commits the transaction.
+    fn commit(me: Bound<'_, Self>) -> Result<(), Self::Error>;
+
+    /// Rolls back the transaction.
+    fn rollback(me: Bound<'_, Self>) -> Result<(), Self::Error>;
+}
+
+/// A transaction which supports savepoints (nested transactions).
+///
+/// Committing a savepoint releases it, rolling it back rolls back the changes
+/// done since it was created. Savepoints have the access mode of their transaction
+/// and can only be created in writable transactions.
+pub trait Savepoint: Transaction {
+    /// The (lifetime erased) savepoint type.
+    type Savepoint: Savepoint<Error=Self::Error, Access=Self::Access>;
+
+    /// Creates a new savepoint bound to this transaction.
+    fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, Self::Savepoint>, Self::Error>
+        where Self::Access: Writable;
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: Savepoint
+{
+    /// Runs `f` in a new savepoint of `me`, keeping it's changes only if `f` succeeds.
+    ///
+    /// The savepoint is committed (i.e. released) if `f` returns `Ok` and rolled back
+    /// if it returns `Err`, so "try this, keep it only if it worked" can be written
+    /// once for all backends. If creating or committing the savepoint fails that error
+    /// is returned. If rolling it back fails the error of `f` is returned anyway, as
+    /// it's the cause of the rollback.
+    ///
+    /// This is a associated function to not shadow methods of `T` available
+    /// through `Deref`, i.e. it has to be called as `Bound::speculate(trans, f)`.
+    pub fn speculate<R, E, F>(me: &mut Self, f: F) -> Result<R, E>
+        where T::Access: Writable,
+              E: From<T::Error>,
+              F: FnOnce(&mut Bound<'_, T::Savepoint>) -> Result<R, E>
+    {
+        let mut savepoint = Savepoint::savepoint(me)?;
+        let res = f(&mut savepoint);
+        Transaction2::finish(savepoint, res)
+    }
+}
+
+/// Extension methods for all [`Connection`]s, see [Extending the traits](self#extending-the-traits).
+pub trait Connection2: Connection + sealed::Connection {
+
+    /// Runs `f` in a new transaction, committing it if `f` returns `Ok` and rolling it back otherwise.
+    ///
+    /// See [`Transaction2::finish()`] for which error is returned.
+    fn transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+        where E: From<Self::Error>,
+              F: FnOnce(&mut Bound<'_, Self::Transaction>) -> Result<R, E>
+    {
+        let mut trans = self.begin()?;
+        let res = f(&mut trans);
+        Transaction2::finish(trans, res)
+    }
+
+    /// Checks that the connection works by beginning a transaction and rolling it back.
+    fn check(&mut self) -> Result<(), Self::Error> {
+        let trans = self.begin()?;
+        Transaction::rollback(trans)
+    }
+}
+
+impl<C: ?Sized + Connection> Connection2 for C {}
+
+/// Extension methods for all [`ReadConnection`]s, see [Extending the traits](self#extending-the-traits).
+pub trait ReadConnection2: ReadConnection + sealed::ReadConnection {
+
+    /// Runs `f` in a new read-only transaction, see [`Connection2::transaction()`].
+    fn read_transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+        where E: From<Self::Error>,
+              F: FnOnce(&mut Bound<'_, Self::ReadTransaction>) -> Result<R, E>
+    {
+        let mut trans = self.begin_read()?;
+        let res = f(&mut trans);
+        Transaction2::finish(trans, res)
+    }
+}
+
+impl<C: ?Sized + ReadConnection> ReadConnection2 for C {}
+
+/// Extension functions for all [`Transaction`]s, see [Extending the traits](self#extending-the-traits).
+pub trait Transaction2: Transaction + sealed::Transaction {
+
+    /// Commits the transaction if `res` is `Ok` and rolls it back otherwise.
+    ///
+    /// If committing fails that error is returned. If rolling back fails the error
+    /// in `res` is returned anyway, as it's the cause of the rollback.
+    fn finish<R, E>(me: Bound<'_, Self>, res: Result<R, E>) -> Result<R, E>
+        where E: From<Self::Error>
+    {
+        match res {
+            Ok(value) => {
+                Transaction::commit(me)?;
+                Ok(value)
+            },
+            Err(err) => {
+                let _ = Transaction::rollback(me);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T: Transaction> Transaction2 for T {}
+
+/// Method syntax for [`Transaction`], i.e. `trans.commit()` (requires the `nightly` feature).
+///
+/// This uses the unstable "arbitrary self types" feature (`self: Bound<'_, Self>`), code
+/// calling the methods doesn't need to enable it. It is implemented for all transactions.
+///
+/// ```
+/// use galemu::{Bound, traits::{Connection, TransactionExt}};
+///
+/// fn begin_and_commit<C: Connection>(conn: &mut C) -> Result<(), C::Error> {
+///     conn.begin()?.commit()
+/// }
+/// ```
+#[cfg(feature = "nightly")]
+pub trait TransactionExt: Transaction {
+    /// Commits the transaction, see [`Transaction::commit`].
+    fn commit(self: Bound<'_, Self>) -> Result<(), Self::Error> {
+        Transaction::commit(self)
+    }
+
+    /// Rolls back the transaction, see [`Transaction::rollback`].
+    fn rollback(self: Bound<'_, Self>) -> Result<(), Self::Error> {
+        Transaction::rollback(self)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Transaction> TransactionExt for T {}
+
+/// Method syntax for [`Savepoint`], i.e. `trans.savepoint()` (requires the `nightly` feature).
+///
+/// See [`TransactionExt`].
+#[cfg(feature = "nightly")]
+pub trait SavepointExt: Savepoint {
+    /// Creates a new savepoint bound to this transaction, see [`Savepoint::savepoint`].
+    fn savepoint<'b>(self: &'b mut Bound<'_, Self>) -> Result<Bound<'b, Self::Savepoint>, Self::Error>
+        where Self::Access: Writable
+    {
+        Savepoint::savepoint(self)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Savepoint> SavepointExt for T {}
+
+/// Implements a connection-style trait for `&mut C` and `Box<C>` by forwarding to `C`.
+///
+/// As `macro_rules` can't look up the methods of a trait by it's name the names of
+/// the associated types and the signatures of the methods have to be repeated.
+/// Only methods taking `&self` or `&mut self` with (optional) lifetime parameters
+/// are supported, which is enough for traits returning `Bound` values (e.g. with
+/// `Bound<'_, Self::Transaction>` in the return type). The impls are also
+/// implemented for unsized types, i.e. they cover `&mut dyn Trait` and `Box<dyn Trait>`
+/// if the trait is object safe.
+///
+/// Associated types bound by `for<'a> BoundExt<'a>` (which implies `'static`) need
+/// an explicit `'static` bound, as the compiler can't derive it in the generated impls.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, BoundExt};
+///
+/// trait GeneralConnection {
+///     type Transaction: for<'a> BoundExt<'a> + 'static;
+///
+///     fn create_transaction(&mut self) -> Bound<'_, Self::Transaction>;
+///     fn name(&self) -> &str;
+/// }
+///
+/// gal_blanket_impls! {
+///     impl GeneralConnection {
+///         type Transaction;
+///         fn create_transaction(&mut self) -> Bound<'_, Self::Transaction>;
+///         fn name(&self) -> &str;
+///     }
+/// }
+///
+/// fn name_of(conn: impl GeneralConnection) -> String {
+///     conn.name().to_owned()
+/// }
+///
+/// struct Connection;
+/// struct Transaction<'conn>(&'conn mut Connection);
+///
+/// create_gal_wrapper_type!{ struct TransactionWrap(Transaction<'a>); }
+///
+/// impl GeneralConnection for Connection {
+///     type Transaction = TransactionWrap;
+///
+///     fn create_transaction(&mut self) -> Bound<'_, TransactionWrap> {
+///         TransactionWrap::new(Transaction(self))
+///     }
+///
+///     fn name(&self) -> &str {
+///         "conn"
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut conn = Connection;
+/// assert_eq!(name_of(&mut conn), "conn");
+/// assert_eq!(name_of(Box::new(conn)), "conn");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! gal_blanket_impls {
+    (impl $Trait:ident { $($items:tt)* }) => (
+        impl<'r, C: $Trait + ?Sized> $Trait for &'r mut C {
+            $crate::gal_blanket_impls!{ @items $Trait $($items)* }
+        }
+
+        impl<C: $Trait + ?Sized> $Trait for ::std::boxed::Box<C> {
+            $crate::gal_blanket_impls!{ @items $Trait $($items)* }
+        }
+    );
+
+    (@items $Trait:ident) => ();
+
+    (@items $Trait:ident type $assoc:ident; $($rest:tt)*) => (
+        type $assoc = <C as $Trait>::$assoc;
+
+        $crate::gal_blanket_impls!{ @items $Trait $($rest)* }
+    );
+
+    (@items $Trait:ident
+        fn $name:ident $(<$($lt:lifetime),*>)* (&mut self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)*;
+        $($rest:tt)*
+    ) => (
+        fn $name $(<$($lt),*>)* (&mut self $(, $arg: $ty)*) $(-> $ret)* {
+            <C as $Trait>::$name(&mut **self $(, $arg)*)
+        }
+
+        $crate::gal_blanket_impls!{ @items $Trait $($rest)* }
+    );
+
+    (@items $Trait:ident
+        fn $name:ident $(<$($lt:lifetime),*>)* (&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)*;
+        $($rest:tt)*
+    ) => (
+        fn $name $(<$($lt),*>)* (&self $(, $arg: $ty)*) $(-> $ret)* {
+            <C as $Trait>::$name(&**self $(, $arg)*)
+        }
+
+        $crate::gal_blanket_impls!{ @items $Trait $($rest)* }
+    );
+}
+
+gal_blanket_impls! {
+    impl Connection {
+        type Error;
+        type Transaction;
+        fn begin(&mut self) -> Result<Bound<'_, Self::Transaction>, Self::Error>;
+    }
+}
+
+gal_blanket_impls! {
+    impl ReadConnection {
+        type ReadTransaction;
+        fn begin_read(&mut self) -> Result<Bound<'_, Self::ReadTransaction>, Self::Error>;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use create_gal_wrapper_type;
+    use super::*;
+
+    struct Store {
+        value: u32
+    }
+
+    /// Stages a new value which is written to `target` on commit.
+    struct Trans<'a> {
+        target: &'a mut u32,
+        value: u32
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Trans<'a>); }
+
+    /// Reads the value, committing does nothing.
+    struct ReadTrans<'a> {
+        value: &'a u32
+    }
+
+    create_gal_wrapper_type!{ struct ReadTransWrap(ReadTrans<'a>); }
+
+    impl Connection for Store {
+        type Error = ();
+        type Transaction = TransWrap;
+
+        fn begin(&mut self) -> Result<Bound<'_, TransWrap>, ()> {
+            Ok(TransWrap::new(Trans { value: self.value, target: &mut self.value }))
+        }
+    }
+
+    impl ReadConnection for Store {
+        type ReadTransaction = ReadTransWrap;
+
+        fn begin_read(&mut self) -> Result<Bound<'_, ReadTransWrap>, ()> {
+            Ok(ReadTransWrap::new(ReadTrans { value: &self.value }))
+        }
+    }
+
+    impl AccessMode for TransWrap {
+        type Access = ReadWrite;
+    }
+
+    impl AccessMode for ReadTransWrap {
+        type Access = ReadOnly;
+    }
+
+    impl Transaction for TransWrap {
+        type Error = ();
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), ()> {
+            let trans = TransWrap::into_inner(me);
+            *trans.target = trans.value;
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), ()> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    impl Transaction for ReadTransWrap {
+        type Error = ();
+
+        fn commit(me: Bound<'_, Self>) -> Result<(), ()> {
+            drop(me);
+            Ok(())
+        }
+
+        fn rollback(me: Bound<'_, Self>) -> Result<(), ()> {
+            drop(me);
+            Ok(())
+        }
+    }
+
+    impl Savepoint for TransWrap {
+        type Savepoint = TransWrap;
+
+        fn savepoint<'b>(me: &'b mut Bound<'_, Self>) -> Result<Bound<'b, TransWrap>, ()> {
+            let outer = TransWrap::get_mut(me);
+            Ok(TransWrap::new(Trans { value: outer.value, target: &mut outer.value }))
+        }
+    }
+
+    fn with_savepoint<C>(conn: &mut C, commit_savepoint: bool) -> Result<(), C::Error>
+        where C: Connection, C::Transaction: Savepoint
+    {
+        let mut trans = conn.begin()?;
+        let savepoint = Savepoint::savepoint(&mut trans)?;
+        if commit_savepoint {
+            Transaction::commit(savepoint)?;
+        } else {
+            Transaction::rollback(savepoint)?;
+        }
+        Transaction::commit(trans)
+    }
+
+    /// Writes `value` in a savepoint, only compiles for writable transactions.
+    fn write<T>(trans: &mut Bound<'_, T>, value: u32) -> Result<(), ()>
+        where T: Savepoint<Error=(), Savepoint=TransWrap>, T::Access: Writable
+    {
+        let mut savepoint = Savepoint::savepoint(trans)?;
+        TransWrap::get_mut(&mut savepoint).value = value;
+        Transaction::commit(savepoint)
+    }
+
+    fn begin_twice<C: Connection>(mut conn: C) -> Result<(), C::Error> {
+        Transaction::commit(conn.begin()?)?;
+        Transaction::commit(conn.begin()?)
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn transactions_can_be_used_with_method_syntax() {
+        let mut store = Store { value: 1 };
+        {
+            let mut trans = store.begin().unwrap();
+            let mut savepoint = trans.savepoint().unwrap();
+            TransWrap::get_mut(&mut savepoint).value = 4;
+            savepoint.commit().unwrap();
+            trans.commit().unwrap();
+        }
+        assert_eq!(store.value, 4);
+        store.begin().unwrap().rollback().unwrap();
+        assert_eq!(store.value, 4);
+    }
+
+    #[test]
+    fn connections_can_be_passed_by_reference_or_box() {
+        let mut store = Store { value: 1 };
+        begin_twice(&mut store).unwrap();
+        begin_twice(&mut &mut store).unwrap();
+        begin_twice(Box::new(store)).unwrap();
+    }
+
+    #[test]
+    fn generic_code_can_use_the_traits() {
+        let mut store = Store { value: 1 };
+        {
+            let mut trans = store.begin().unwrap();
+            {
+                let mut savepoint = Savepoint::savepoint(&mut trans).unwrap();
+                TransWrap::get_mut(&mut savepoint).value = 3;
+                Transaction::rollback(savepoint).unwrap();
+            }
+            {
+                let mut savepoint = Savepoint::savepoint(&mut trans).unwrap();
+                TransWrap::get_mut(&mut savepoint).value = 2;
+                Transaction::commit(savepoint).unwrap();
+            }
+            Transaction::commit(trans).unwrap();
+        }
+        assert_eq!(store.value, 2);
+        with_savepoint(&mut store, true).unwrap();
+        with_savepoint(&mut store, false).unwrap();
+        assert_eq!(store.value, 2);
+    }
+
+    #[test]
+    fn speculative_changes_are_only_kept_on_success() {
+        let mut store = Store { value: 1 };
+        {
+            let mut trans = store.begin().unwrap();
+            let res = Bound::speculate(&mut trans, |savepoint| {
+                TransWrap::get_mut(savepoint).value = 2;
+                Ok::<_, ()>("kept")
+            });
+            assert_eq!(res, Ok("kept"));
+            let res = Bound::speculate(&mut trans, |savepoint| {
+                TransWrap::get_mut(savepoint).value = 3;
+                Err::<(), _>(())
+            });
+            assert_eq!(res, Err(()));
+            assert_eq!(TransWrap::get(&trans).value, 2);
+            Transaction::commit(trans).unwrap();
+        }
+        assert_eq!(store.value, 2);
+    }
+
+    #[test]
+    fn extension_traits_are_implemented_for_all_connections() {
+        let mut store = Store { value: 1 };
+        let res = store.transaction(|trans| {
+            TransWrap::get_mut(trans).value = 2;
+            Ok::<_, ()>("committed")
+        });
+        assert_eq!(res, Ok("committed"));
+        let res = store.transaction(|trans| {
+            TransWrap::get_mut(trans).value = 3;
+            Err::<(), _>(())
+        });
+        assert_eq!(res, Err(()));
+        assert_eq!(store.value, 2);
+        assert_eq!(store.read_transaction(|trans| Ok::<_, ()>(*ReadTransWrap::get(trans).value)), Ok(2));
+        assert_eq!(Box::new(store).check(), Ok(()));
+    }
+
+    #[test]
+    fn access_modes_are_part_of_the_traits() {
+        let mut store = Store { value: 1 };
+        {
+            let mut trans = store.begin().unwrap();
+            write(&mut trans, 5).unwrap();
+            Transaction::commit(trans).unwrap();
+        }
+        let mut by_ref = &mut store;
+        let trans = ReadConnection::begin_read(&mut by_ref).unwrap();
+        assert_eq!(*ReadTransWrap::get(&trans).value, 5);
+        Transaction::commit(trans).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use {Bound, BoundExt};
+
+/// The validity state tracked in `Bound` with the `validity_checks` feature in debug builds.
+#[cfg(all(feature = "validity_checks", debug_assertions))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Validity {
+    Valid,
+    PreDropped,
+    Consumed
+}
+
+#[cfg(all(feature = "validity_checks", debug_assertions))]
+impl Validity {
+    fn check<T>(self) {
+        match self {
+            Validity::Valid => (),
+            Validity::PreDropped => panic!(
+                "bound value of type `{}` used after `pre_drop` was run", ::std::any::type_name::<T>()
+            ),
+            Validity::Consumed => panic!(
+                "bound value of type `{}` used after it's inner value was moved out", ::std::any::type_name::<T>()
+            )
+        }
+    }
+}
+
+/// Checks for wrappers catching use-after-invalidation bugs.
+///
+/// With the `validity_checks` feature `Bound` tracks (in debug builds only) if its
+/// inner value was pre-dropped or moved out. Accessing the inner value through
+/// `Deref` or `_get_mut` afterwards panics with a message naming the type, instead
+/// of silently using a dropped value. Without the feature or in release builds the
+/// state is not stored and all of this functions are no-ops.
+///
+/// Wrappers created with [`create_gal_wrapper_type`](crate::create_gal_wrapper_type)
+/// and all other `BoundExt` implementations of this crate (incl. the default `pre_drop`)
+/// mark themselves as pre-dropped in `pre_drop` (so e.g. running `pre_drop` twice panics
+/// instead of dropping the inner value twice). `Bound::_into_inner` (and with it e.g.
+/// `Bound::unzip` and `Bound::unbox`) checks the state and marks the value as consumed,
+/// as does `Bound::forget`. Hand-written wrappers should call [`Bound::mark_pre_dropped()`]
+/// at the end of their `pre_drop` and [`Bound::mark_consumed()`] if they move the inner
+/// value out in any other way:
+///
+/// ```
+/// use std::mem::ManuallyDrop;
+/// use galemu::{Bound, BoundExt};
+///
+/// struct TransWrap {
+///     log: ManuallyDrop<Vec<&'static str>>
+/// }
+///
+/// impl<'a> BoundExt<'a> for TransWrap {
+///     #[allow(unsafe_code)]
+///     unsafe fn pre_drop(me: &mut Bound<'a, Self>) {
+///         ManuallyDrop::drop(&mut me._get_mut().log);
+///         Bound::mark_pre_dropped(me);
+///     }
+/// }
+/// ```
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+    /// Panics if the inner value was pre-dropped or moved out (if the state is tracked).
+    #[allow(unused_variables)]
+    pub fn assert_valid(me: &Self) {
+        #[cfg(all(feature = "validity_checks", debug_assertions))]
+        me.validity.check::<T>();
+    }
+
+    /// Marks the inner value as pre-dropped, it must not be accessed afterwards.
+    #[allow(unused_variables)]
+    pub fn mark_pre_dropped(me: &mut Self) {
+        #[cfg(all(feature = "validity_checks", debug_assertions))]
+        { me.validity = Validity::PreDropped; }
+    }
+
+    /// Marks the inner value as moved out, it must not be accessed afterwards.
+    #[allow(unused_variables)]
+    pub fn mark_consumed(me: &mut Self) {
+        #[cfg(all(feature = "validity_checks", debug_assertions))]
+        { me.validity = Validity::Consumed; }
+    }
+
+    /// Returns false if `pre_drop` must not be run as the inner value is no longer valid.
+    ///
+    /// Panics instead, unless the thread already panics (which would abort).
+    pub(crate) fn valid_for_drop(&self) -> bool {
+        #[cfg(all(feature = "validity_checks", debug_assertions))]
+        {
+            if self.validity != Validity::Valid {
+                if !::std::thread::panicking() {
+                    self.validity.check::<T>();
+                }
+                return false;
+            }
+        }
+        true
+    }
+}
+
+
+#[cfg(all(test, feature = "validity_checks", debug_assertions))]
+mod test {
+    use std::{
+        mem::ManuallyDrop,
+        panic::{self, AssertUnwindSafe}
+    };
+    use create_gal_wrapper_type;
+    use super::*;
+
+    struct Transaction<'conn> {
+        log: &'conn mut Vec<&'static str>
+    }
+
+    create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+
+    fn panic_message<R>(func: impl FnOnce() -> R) -> String {
+        let error = panic::catch_unwind(AssertUnwindSafe(func)).err().expect("expected a panic");
+        error.downcast::<String>().map(|msg| *msg).unwrap_or_default()
+    }
+
+    /// Runs `pre_drop` and returns the value, which must not be dropped afterwards.
+    fn pre_dropped<'a, T: BoundExt<'a>>(bound: Bound<'a, T>) -> ManuallyDrop<Bound<'a, T>> {
+        let mut bound = ManuallyDrop::new(bound);
+        unsafe_block! {
+            "`pre_drop` is run once and the value is not dropped afterwards" => {
+                BoundExt::pre_drop(&mut *bound);
+            }
+        }
+        bound
+    }
+
+    /// Checks that `bound` was marked as pre-dropped, then drops the inner value.
+    fn assert_pre_dropped<'a, T: BoundExt<'a>>(bound: ManuallyDrop<Bound<'a, T>>) {
+        let msg = panic_message(|| Bound::assert_valid(&bound));
+        assert!(msg.contains("used after `pre_drop` was run"), "{}", msg);
+        drop(ManuallyDrop::into_inner(bound).into_inner_untraced());
+    }
+
+    #[test]
+    fn pre_dropped_values_can_not_be_used() {
+        let mut log = Vec::new();
+        // never dropped as this would run `pre_drop` again
+        let mut trans = ManuallyDrop::new(TransWrap::new(Transaction { log: &mut log }));
+        TransWrap::get_mut(&mut trans).log.push("insert");
+        unsafe_block! {
+            "`pre_drop` is run once, the second run is expected to panic" => {
+                BoundExt::pre_drop(&mut *trans);
+                let msg = panic_message(|| BoundExt::pre_drop(&mut *trans));
+                assert!(msg.contains("used after `pre_drop` was run"), "{}", msg);
+            }
+        }
+        let msg = panic_message(|| TransWrap::get(&trans).log.len());
+        assert!(msg.contains("TransWrap"), "{}", msg);
+        assert_eq!(log, ["insert"]);
+    }
+
+    #[test]
+    fn consumed_values_can_not_be_used() {
+        let mut log = Vec::new();
+        let mut trans = ManuallyDrop::new(TransWrap::new(Transaction { log: &mut log }));
+        Bound::mark_consumed(&mut trans);
+        let msg = panic_message(|| TransWrap::get_mut(&mut trans).log.push("insert"));
+        assert!(msg.contains("moved out"), "{}", msg);
+        let trans = ManuallyDrop::into_inner(trans);
+        let msg = panic_message(|| TransWrap::into_inner(trans));
+        assert!(msg.contains("moved out"), "{}", msg);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn crate_provided_pre_drops_mark_values() {
+        let (mut log1, mut log2) = (Vec::new(), Vec::new());
+        let zipped = Bound::zip(
+            TransWrap::new(Transaction { log: &mut log1 }),
+            TransWrap::new(Transaction { log: &mut log2 })
+        );
+        assert_pre_dropped(pre_dropped(zipped));
+        let mut log = Vec::new();
+        assert_pre_dropped(pre_dropped(Bound::boxed(TransWrap::new(Transaction { log: &mut log }))));
+        let mut log = Vec::new();
+        let mut takeable = pre_dropped(Bound::takeable(TransWrap::new(Transaction { log: &mut log })));
+        let msg = panic_message(|| Bound::take(&mut takeable).is_some());
+        assert!(msg.contains("Takeable"), "{}", msg);
+        assert_pre_dropped(takeable);
+        let value = 1;
+        assert_pre_dropped(pre_dropped(Bound::<&u32>::from(&value)));
+
+        #[cfg(feature = "std_wrappers")]
+        {
+            use std::sync::Mutex;
+            use std_wrappers::MutexGuardWrap;
+
+            let mutex = Mutex::new(1);
+            let guard = pre_dropped(MutexGuardWrap::new(mutex.lock().unwrap()));
+            assert_pre_dropped(guard);
+            assert!(mutex.try_lock().is_ok());
+        }
+
+        #[cfg(feature = "diesel")]
+        {
+            use diesel::{Connection, SqliteConnection};
+            use integrations::diesel::TransactionWrap as DieselTransactionWrap;
+
+            let mut conn = SqliteConnection::establish(":memory:").unwrap();
+            assert_pre_dropped(pre_dropped(DieselTransactionWrap::begin(&mut conn).unwrap()));
+        }
+    }
+
+    #[test]
+    fn consuming_pre_dropped_values_panics() {
+        let (mut log1, mut log2) = (Vec::new(), Vec::new());
+        let zipped = pre_dropped(Bound::zip(
+            TransWrap::new(Transaction { log: &mut log1 }),
+            TransWrap::new(Transaction { log: &mut log2 })
+        ));
+        let msg = panic_message(|| Bound::unzip(ManuallyDrop::into_inner(zipped)));
+        assert!(msg.contains("used after `pre_drop` was run"), "{}", msg);
+
+        let mut log = Vec::new();
+        let boxed = pre_dropped(Bound::boxed(TransWrap::new(Transaction { log: &mut log })));
+        let msg = panic_message(|| Bound::unbox(ManuallyDrop::into_inner(boxed)));
+        assert!(msg.contains("Box"), "{}", msg);
+
+        let mut log = Vec::new();
+        let trans = pre_dropped(TransWrap::new(Transaction { log: &mut log }));
+        let msg = panic_message(|| Bound::takeable(ManuallyDrop::into_inner(trans)));
+        assert!(msg.contains("TransWrap"), "{}", msg);
+    }
+}
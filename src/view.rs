@@ -0,0 +1,215 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut}
+};
+
+use {Bound, BoundExt};
+
+/// A shared view of a bound value, see [`Bound::as_ref()`].
+///
+/// This is like a `&'b Bound<'a, T>` but allows functions to explicitly demand
+/// "a view of a bound value" in their signature (instead of a reference to e.g.
+/// the target of `Bound`'s `Deref` impl which no longer is coupled to `'a`).
+/// It derefs to the `Bound`, so the functions of wrapper types (e.g. `get`)
+/// can be used with it.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, BoundRef};
+///
+/// struct Transaction<'conn> { id: &'conn u32 }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// fn log_id(trans: BoundRef<TransWrap>) -> String {
+///     format!("transaction {}", TransWrap::get(&trans).id)
+/// }
+///
+/// # fn main() {
+/// let id = 12;
+/// let trans = TransWrap::new(Transaction { id: &id });
+/// assert_eq!(log_id(Bound::as_ref(&trans)), "transaction 12");
+/// assert_eq!(log_id(Bound::as_ref(&trans)), "transaction 12");
+/// # }
+/// ```
+pub struct BoundRef<'b, 'a: 'b, T: BoundExt<'a> + 'b> {
+    bound: &'b Bound<'a, T>
+}
+
+impl<'b, 'a, T> BoundRef<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    /// Returns the reference to the bound value this is a view of.
+    pub fn into_ref(me: Self) -> &'b Bound<'a, T> {
+        me.bound
+    }
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+    /// Returns a shared view of this bound value.
+    pub fn as_ref<'b>(me: &'b Self) -> BoundRef<'b, 'a, T> {
+        BoundRef { bound: me }
+    }
+}
+
+impl<'b, 'a, T> Deref for BoundRef<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    type Target = Bound<'a, T>;
+
+    fn deref(&self) -> &Bound<'a, T> {
+        self.bound
+    }
+}
+
+impl<'b, 'a, T> Clone for BoundRef<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'b, 'a, T> Copy for BoundRef<'b, 'a, T>
+    where T: BoundExt<'a>
+{}
+
+impl<'b, 'a, T> fmt::Debug for BoundRef<'b, 'a, T>
+    where T: BoundExt<'a> + fmt::Debug
+{
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.debug_tuple("BoundRef").field(&**self.bound).finish()
+    }
+}
+
+/// A mutable (reborrowed) view of a bound value, see [`Bound::as_mut()`].
+///
+/// This is the `&mut` counterpart of [`BoundRef`], it allows lending a bound value
+/// to a function without moving it in and out of it. It derefs (mutably) to the
+/// `Bound`, so the functions of wrapper types (e.g. `get_mut`) can be used with it.
+/// Like `&mut` references it can be reborrowed to pass it on for a shorter time.
+///
+/// ```
+/// # #[macro_use] extern crate galemu;
+/// use galemu::{Bound, BoundMut};
+///
+/// struct Transaction<'conn> { log: &'conn mut Vec<&'static str> }
+///
+/// create_gal_wrapper_type!{ struct TransWrap(Transaction<'a>); }
+///
+/// fn run_migration(mut trans: BoundMut<TransWrap>, name: &'static str) {
+///     TransWrap::get_mut(&mut trans).log.push(name);
+/// }
+///
+/// fn run_migrations(mut trans: BoundMut<TransWrap>) {
+///     run_migration(BoundMut::reborrow(&mut trans), "users");
+///     run_migration(trans, "posts");
+/// }
+///
+/// # fn main() {
+/// let mut log = Vec::new();
+/// {
+///     let mut trans = TransWrap::new(Transaction { log: &mut log });
+///     run_migrations(Bound::as_mut(&mut trans));
+///     // still usable
+///     TransWrap::get_mut(&mut trans).log.push("commit");
+/// }
+/// assert_eq!(log, &["users", "posts", "commit"]);
+/// # }
+/// ```
+pub struct BoundMut<'b, 'a: 'b, T: BoundExt<'a> + 'b> {
+    bound: &'b mut Bound<'a, T>
+}
+
+impl<'b, 'a, T> BoundMut<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    /// Reborrows the view for a shorter lifetime.
+    pub fn reborrow<'c>(me: &'c mut Self) -> BoundMut<'c, 'a, T> {
+        BoundMut { bound: &mut *me.bound }
+    }
+
+    /// Returns a shared view of the bound value.
+    pub fn as_ref<'c>(me: &'c Self) -> BoundRef<'c, 'a, T> {
+        BoundRef { bound: &*me.bound }
+    }
+
+    /// Returns the reference to the bound value this is a view of.
+    pub fn into_mut(me: Self) -> &'b mut Bound<'a, T> {
+        me.bound
+    }
+}
+
+impl<'a, T> Bound<'a, T>
+    where T: BoundExt<'a>
+{
+    /// Returns a mutable view of this bound value.
+    pub fn as_mut<'b>(me: &'b mut Self) -> BoundMut<'b, 'a, T> {
+        BoundMut { bound: me }
+    }
+}
+
+impl<'b, 'a, T> Deref for BoundMut<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    type Target = Bound<'a, T>;
+
+    fn deref(&self) -> &Bound<'a, T> {
+        self.bound
+    }
+}
+
+impl<'b, 'a, T> DerefMut for BoundMut<'b, 'a, T>
+    where T: BoundExt<'a>
+{
+    fn deref_mut(&mut self) -> &mut Bound<'a, T> {
+        self.bound
+    }
+}
+
+impl<'b, 'a, T> fmt::Debug for BoundMut<'b, 'a, T>
+    where T: BoundExt<'a> + fmt::Debug
+{
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.debug_tuple("BoundMut").field(&**self.bound).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use Static;
+    use super::*;
+
+    fn sum(values: BoundRef<Static<Vec<u32>>>) -> u32 {
+        Static::get(&values).iter().sum()
+    }
+
+    #[test]
+    fn views_can_be_copied() {
+        let values = Bound::new_static(vec![1, 2, 3]);
+        let view = Bound::as_ref(&values);
+        let copy = view;
+        assert_eq!(sum(view) + sum(copy), 12);
+        assert_eq!(BoundRef::into_ref(view).len(), 3);
+        assert_eq!(format!("{:?}", view), "BoundRef(Static([1, 2, 3]))");
+    }
+
+    fn push_twice(mut values: BoundMut<Static<Vec<u32>>>, value: u32) {
+        Static::get_mut(&mut values).push(value);
+        Static::get_mut(&mut BoundMut::reborrow(&mut values)).push(value);
+        assert_eq!(sum(BoundMut::as_ref(&values)), 2 * value);
+    }
+
+    #[test]
+    fn mutable_views_can_be_reborrowed() {
+        let mut values = Bound::new_static(Vec::new());
+        push_twice(Bound::as_mut(&mut values), 3);
+        let view = Bound::as_mut(&mut values);
+        assert_eq!(format!("{:?}", view), "BoundMut(Static([3, 3]))");
+        Static::get_mut(BoundMut::into_mut(view)).clear();
+        assert!(values.is_empty());
+    }
+}